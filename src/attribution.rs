@@ -0,0 +1,139 @@
+use std::collections::{BTreeSet, HashMap};
+use std::io::{self, Write};
+
+use itertools::Itertools;
+
+use crate::{read_license_texts, DepTable, LicenseTable, Licenses};
+
+const RULE: &str =
+    "================================================================================";
+const SEPARATOR: &str =
+    "--------------------------------------------------------------------------------";
+
+/// License as it should read in the notices: the full SPDX expression
+/// rather than the list of its licenses.
+fn license_line(licenses: &Licenses<'_>) -> String {
+    match licenses {
+        Licenses::List(expression) => expression.canonical(),
+        licenses => licenses.to_string(),
+    }
+}
+
+/// SPDX identifiers of the licenses a crate declares or was detected to
+/// have, in the order of its `license` field.
+fn license_ids(licenses: &Licenses<'_>) -> Vec<&'static str> {
+    match licenses {
+        Licenses::List(expression) => expression
+            .0
+            .requirements()
+            .filter_map(|er| er.req.license.id())
+            .map(|id| id.name)
+            .unique()
+            .collect(),
+        Licenses::File(_, Some(detected)) => vec![detected.license.name],
+        _ => Vec::new(),
+    }
+}
+
+/// Match a license file to one of the licenses of the crate by its name, so
+/// that `LICENSE-MIT` pairs with `MIT` and `LICENSE-APACHE.md` with
+/// `Apache-2.0`. Plain `LICENSE` or `COPYING` files don't say.
+fn license_of_file(file: &str, ids: &[&'static str]) -> Option<usize> {
+    let file = file.to_ascii_lowercase();
+    let stem = [".md", ".txt"]
+        .iter()
+        .find_map(|extension| file.strip_suffix(extension))
+        .unwrap_or(&file);
+    let suffix = stem
+        .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+        .trim_start_matches(['-', '_', '.']);
+    if suffix.is_empty() {
+        return None;
+    }
+
+    ids.iter()
+        .position(|id| id.to_ascii_lowercase().starts_with(suffix))
+}
+
+/// Write a third-party notices document with the license texts of each
+/// crate, suitable for shipping along with a product.
+pub fn write(
+    out: &mut impl Write,
+    depencies_list: &[DepTable<'_>],
+    licenses_list: &BTreeSet<LicenseTable>,
+    max_license_bytes: Option<u64>,
+) -> io::Result<()> {
+    // Versions folded into one row share its licenses
+    let deps: HashMap<(&str, String), &DepTable> = depencies_list
+        .iter()
+        .flat_map(|dep| {
+            dep.other_versions
+                .iter()
+                .map(ToString::to_string)
+                .chain([dep.version.clone()])
+                .map(move |version| ((dep.name.as_str(), version), dep))
+        })
+        .collect();
+
+    writeln!(out, "THIRD-PARTY SOFTWARE NOTICES")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "This product includes the following third-party software. The license"
+    )?;
+    writeln!(out, "terms of each component are reproduced below.")?;
+
+    for (table, texts) in read_license_texts(licenses_list, max_license_bytes) {
+        let Some(dep) = deps.get(&(table.name.as_str(), table.version.clone())) else {
+            continue;
+        };
+
+        writeln!(out)?;
+        writeln!(out, "{RULE}")?;
+        writeln!(out, "{} {}", table.name, table.version)?;
+        writeln!(out, "License: {}", license_line(&dep.licenses))?;
+        if !dep.authors.is_empty() {
+            writeln!(out, "Authors: {}", dep.authors.join(", "))?;
+        }
+        if let Some(ref repository) = dep.package.repository {
+            writeln!(out, "Repository: {repository}")?;
+        }
+        writeln!(out, "{RULE}")?;
+
+        if texts.is_empty() {
+            writeln!(out)?;
+            writeln!(out, "No license text was found for this component.")?;
+        }
+
+        // Texts of the licenses in the order of the `license` field, then
+        // any other files such as `COPYRIGHT` by name
+        let ids = license_ids(&dep.licenses);
+        let mut files: Vec<(Option<usize>, &str, &Vec<u8>)> = texts
+            .iter()
+            .map(|(path, text)| {
+                let file = path.file_name().unwrap_or(path.as_str());
+                (license_of_file(file, &ids), file, text)
+            })
+            .collect();
+        files.sort_by_key(|&(license, _, _)| license.unwrap_or(usize::MAX));
+
+        for (i, (license, file, text)) in files.into_iter().enumerate() {
+            if i > 0 {
+                writeln!(out)?;
+                writeln!(out, "{SEPARATOR}")?;
+            }
+            writeln!(out)?;
+            match license {
+                Some(license) => writeln!(out, "{} ({file}):", ids[license])?,
+                None => writeln!(out, "{file}:")?,
+            }
+            writeln!(out)?;
+            out.write_all(text)?;
+            if !text.ends_with(b"\n") {
+                writeln!(out)?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use spdx::expression::{ExprNode, Operator};
+use spdx::LicenseReq;
+
+use crate::{DepTable, Licenses};
+
+const GPL: &[&str] = &[
+    "GPL-2.0-only",
+    "GPL-2.0-or-later",
+    "GPL-3.0-only",
+    "GPL-3.0-or-later",
+];
+
+/// Pairs of licenses whose terms are widely held to conflict when both have
+/// to be complied with. Only a heuristic: it's far from complete and doesn't
+/// replace a legal review.
+const INCOMPATIBLE: &[(&[&str], &[&str])] = &[
+    (
+        &["GPL-2.0-only"],
+        &[
+            "Apache-2.0",
+            "AGPL-3.0-only",
+            "AGPL-3.0-or-later",
+            "GPL-3.0-only",
+            "GPL-3.0-or-later",
+            "LGPL-3.0-only",
+            "LGPL-3.0-or-later",
+            "MPL-2.0-no-copyleft-exception",
+        ],
+    ),
+    (
+        GPL,
+        &[
+            "BSD-4-Clause",
+            "CDDL-1.0",
+            "CDDL-1.1",
+            "EPL-1.0",
+            "MPL-1.1",
+            "OpenSSL",
+            "SSPL-1.0",
+        ],
+    ),
+];
+
+fn is_incompatible(a: &LicenseReq, b: &LicenseReq) -> bool {
+    // Exceptions such as `Classpath-exception-2.0` exist to lift the very
+    // restrictions that cause these conflicts
+    if a.addition.is_some() || b.addition.is_some() {
+        return false;
+    }
+    let (Some(a), Some(b)) = (a.license.id(), b.license.id()) else {
+        return false;
+    };
+
+    INCOMPATIBLE.iter().any(|(left, right)| {
+        (left.contains(&a.name) && right.contains(&b.name))
+            || (left.contains(&b.name) && right.contains(&a.name))
+    })
+}
+
+/// The alternatives an expression offers, each the set of licenses that
+/// have to be complied with together. `MIT OR (Apache-2.0 AND ISC)` gives
+/// `[MIT]` and `[Apache-2.0, ISC]`.
+fn alternatives(expression: &spdx::Expression) -> Vec<Vec<&LicenseReq>> {
+    let mut stack: Vec<Vec<Vec<&LicenseReq>>> = Vec::new();
+    for node in expression.iter() {
+        match node {
+            ExprNode::Req(req) => stack.push(vec![vec![&req.req]]),
+            ExprNode::Op(op) => {
+                let (Some(right), Some(left)) = (stack.pop(), stack.pop()) else {
+                    return Vec::new();
+                };
+                stack.push(match op {
+                    Operator::Or => left.into_iter().chain(right).collect(),
+                    Operator::And => left
+                        .iter()
+                        .flat_map(|l| right.iter().map(move |r| [&l[..], &r[..]].concat()))
+                        .collect(),
+                });
+            }
+        }
+    }
+    stack.pop().unwrap_or_default()
+}
+
+/// Pairs of incompatible licenses a crate's expression combines with `AND`,
+/// as in `GPL-2.0-only AND Apache-2.0`.
+pub fn conflicts(licenses: &Licenses<'_>) -> Vec<(String, String)> {
+    let Licenses::List(ref expression) = *licenses else {
+        return Vec::new();
+    };
+
+    let mut conflicts = Vec::new();
+    for alternative in alternatives(&expression.0) {
+        for (i, a) in alternative.iter().enumerate() {
+            for b in &alternative[i + 1..] {
+                let pair = (a.to_string(), b.to_string());
+                if is_incompatible(a, b) && !conflicts.contains(&pair) {
+                    conflicts.push(pair);
+                }
+            }
+        }
+    }
+    conflicts
+}
+
+/// Crates combining licenses known to be incompatible, with the conflicting
+/// pairs.
+pub fn incompatible<'a, 'b>(
+    depencies_list: &'a [DepTable<'b>],
+) -> Vec<(&'a DepTable<'b>, Vec<(String, String)>)> {
+    depencies_list
+        .iter()
+        .map(|dep| (dep, conflicts(&dep.licenses)))
+        .filter(|(_, conflicts)| !conflicts.is_empty())
+        .collect()
+}
@@ -0,0 +1,50 @@
+use cargo_metadata::Metadata;
+use serde::Deserialize;
+use spdx::Licensee;
+
+use crate::Error;
+
+/// Settings from the `[workspace.metadata.cargo-bom]` table of the
+/// workspace's `Cargo.toml`. Command line options take precedence.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Licenses to allow, like `--allow`
+    pub allow: Vec<String>,
+    /// Licenses to deny, like `--deny`
+    pub deny: Vec<String>,
+    /// Default output format, like `--format`
+    pub format: Option<String>,
+}
+
+impl Config {
+    pub fn from_metadata(metadata: &Metadata) -> crate::Result<Config> {
+        match metadata.workspace_metadata.get("cargo-bom") {
+            Some(value) => Config::deserialize(value).map_err(Error::InvalidConfig),
+            None => Ok(Config::default()),
+        }
+    }
+
+    pub fn allow(&self) -> crate::Result<Vec<Licensee>> {
+        parse_licensees(&self.allow, "allow")
+    }
+
+    pub fn deny(&self) -> crate::Result<Vec<Licensee>> {
+        parse_licensees(&self.deny, "deny")
+    }
+}
+
+fn parse_licensees(licenses: &[String], key: &'static str) -> crate::Result<Vec<Licensee>> {
+    licenses
+        .iter()
+        .map(|license| {
+            license
+                .parse()
+                .map_err(|source| Error::InvalidConfigLicense {
+                    license: license.clone(),
+                    key,
+                    source,
+                })
+        })
+        .collect()
+}
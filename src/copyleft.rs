@@ -0,0 +1,85 @@
+use std::io::{self, Write};
+
+use spdx::LicenseReq;
+
+use crate::{DepTable, Licenses, TableStyle};
+
+/// Families of copyleft and other reciprocal licenses, matched against the
+/// start of SPDX identifiers. Both strong copyleft such as the GPL and weak,
+/// file- or library-level copyleft such as the MPL and LGPL are included, as
+/// either needs a closer look before shipping.
+const COPYLEFT_LICENSES: &[&str] = &[
+    "AGPL-",
+    "APSL-",
+    "CC-BY-NC-SA-",
+    "CC-BY-SA-",
+    "CDDL-",
+    "CECILL-",
+    "CPL-",
+    "EPL-",
+    "EUPL-",
+    "GPL-",
+    "LGPL-",
+    "MPL-",
+    "MS-RL",
+    "ODbL-",
+    "OSL-",
+    "RPL-",
+    "Sleepycat",
+    "SSPL-",
+];
+
+/// Licenses that look like a copyleft family but waive its obligations.
+const NOT_COPYLEFT_LICENSES: &[&str] = &["MPL-2.0-no-copyleft-exception"];
+
+fn is_copyleft(req: &LicenseReq) -> bool {
+    let Some(id) = req.license.id() else {
+        return false;
+    };
+
+    COPYLEFT_LICENSES
+        .iter()
+        .any(|prefix| id.name.starts_with(prefix))
+        && !NOT_COPYLEFT_LICENSES.contains(&id.name)
+}
+
+/// Whether a crate can only be used under a copyleft license. A
+/// dual-licensed crate with a permissive option, such as `MIT OR
+/// GPL-2.0-only`, isn't.
+pub fn requires_copyleft(licenses: &Licenses<'_>) -> bool {
+    match *licenses {
+        Licenses::List(ref expression) => !expression.0.evaluate(|req| !is_copyleft(req)),
+        Licenses::File(_, Some(ref detected)) => is_copyleft(&detected.license.into()),
+        Licenses::Invalid(_) | Licenses::File(_, None) | Licenses::Missing => false,
+    }
+}
+
+/// List the dependencies that can only be used under a copyleft license.
+pub fn write(out: &mut impl Write, list: &[DepTable<'_>], style: TableStyle) -> io::Result<()> {
+    let copyleft: Vec<&DepTable> = list
+        .iter()
+        .filter(|dep| requires_copyleft(&dep.licenses))
+        .collect();
+
+    if copyleft.is_empty() {
+        return writeln!(out, "\nNo dependencies require a copyleft license");
+    }
+
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record(["Name", "Version", "Licenses"]);
+    for dep in &copyleft {
+        builder.push_record([
+            dep.name.clone(),
+            dep.version.clone(),
+            dep.licenses.to_string(),
+        ]);
+    }
+
+    writeln!(out, "\nCopyleft licenses:")?;
+    writeln!(out, "{}", crate::render_table(builder, style))?;
+    writeln!(
+        out,
+        "\n{} dependencies require a copyleft license",
+        copyleft.len()
+    )
+}
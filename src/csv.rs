@@ -0,0 +1,22 @@
+use std::io::{self, Write};
+
+use crate::{Column, DepTable};
+
+pub fn write(
+    out: &mut impl Write,
+    depencies_list: &[DepTable<'_>],
+    headers: bool,
+    columns: &[Column],
+) -> io::Result<()> {
+    let mut writer = ::csv::Writer::from_writer(out);
+
+    if headers {
+        writer.write_record(columns.iter().map(|column| column.header().to_lowercase()))?;
+    }
+
+    for dep in depencies_list {
+        writer.write_record(columns.iter().map(|column| column.cell(dep)))?;
+    }
+
+    writer.flush()
+}
@@ -0,0 +1,135 @@
+use std::io::{self, Write};
+
+use cargo_metadata::Package;
+use serde::Serialize;
+
+use crate::{DepTable, Licenses};
+
+const SPEC_VERSION: &str = "1.5";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Bom<'a> {
+    bom_format: &'static str,
+    spec_version: &'static str,
+    version: u32,
+    metadata: Metadata<'a>,
+    components: Vec<Component<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Metadata<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    component: Option<Component<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Component<'a> {
+    #[serde(rename = "type")]
+    kind: ComponentType,
+    #[serde(rename = "bom-ref")]
+    bom_ref: String,
+    name: &'a str,
+    version: String,
+    purl: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    licenses: Vec<LicenseChoice>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ComponentType {
+    Application,
+    Library,
+}
+
+/// A single license is referred to by its SPDX identifier. Anything more
+/// elaborate, such as a choice between licenses or a `WITH` exception, is
+/// kept as one SPDX expression so that `OR` and `AND` don't get lost.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum LicenseChoice {
+    License { license: License },
+    Expression { expression: String },
+}
+
+#[derive(Debug, Serialize)]
+struct License {
+    id: &'static str,
+}
+
+impl From<&crate::LicenseExpression> for LicenseChoice {
+    fn from(expression: &crate::LicenseExpression) -> Self {
+        let requirements = expression.requirements();
+        if let [req] = requirements.iter().collect::<Vec<_>>()[..] {
+            if let (
+                spdx::LicenseItem::Spdx {
+                    id,
+                    or_later: false,
+                },
+                None,
+            ) = (&req.license, &req.addition)
+            {
+                return LicenseChoice::License {
+                    license: License { id: id.name },
+                };
+            }
+        }
+
+        LicenseChoice::Expression {
+            expression: expression.canonical(),
+        }
+    }
+}
+
+impl<'a> Component<'a> {
+    fn new(kind: ComponentType, package: &'a Package) -> Self {
+        let purl = crate::purl(package);
+        Component {
+            kind,
+            bom_ref: purl.clone(),
+            name: &package.name,
+            version: package.version.to_string(),
+            purl,
+            licenses: Vec::new(),
+        }
+    }
+}
+
+impl<'a> From<&'a DepTable<'a>> for Component<'a> {
+    fn from(dep: &'a DepTable<'a>) -> Self {
+        let mut component = Component::new(ComponentType::Library, dep.package);
+
+        // Crates without a valid SPDX expression are emitted without
+        // license information.
+        if let Licenses::List(ref expression) = dep.licenses {
+            component.licenses = vec![LicenseChoice::from(&**expression)];
+        }
+
+        component
+    }
+}
+
+pub fn write(
+    out: &mut impl Write,
+    root: Option<&Package>,
+    list: &[DepTable<'_>],
+) -> io::Result<()> {
+    let component = root.map(|package| Component::new(ComponentType::Application, package));
+
+    let bom = Bom {
+        bom_format: "CycloneDX",
+        spec_version: SPEC_VERSION,
+        version: 1,
+        metadata: Metadata { component },
+        // The root package is the subject of the BOM, not a component of it
+        components: list
+            .iter()
+            .filter(|dep| root.is_none_or(|root| root.id != dep.package.id))
+            .map(Component::from)
+            .collect(),
+    };
+
+    serde_json::to_writer_pretty(&mut *out, &bom)?;
+    out.write_all(b"\n")
+}
@@ -0,0 +1,51 @@
+use std::fmt;
+use std::fs;
+
+use cargo_metadata::camino::Utf8Path;
+use spdx::detection::{Store, TextData};
+
+/// Matches below this score are too uncertain to report.
+const MIN_CONFIDENCE: f32 = 0.9;
+
+/// License identified from the text of a license file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Detected {
+    pub license: spdx::LicenseId,
+    /// Confidence of the match in percent
+    pub confidence: u8,
+}
+
+impl fmt::Display for Detected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{} (detected, {}%)", self.license.name, self.confidence)
+    }
+}
+
+/// Identifies licenses by comparing texts against the SPDX license list.
+pub struct Detector {
+    store: Store,
+}
+
+impl Detector {
+    pub fn new() -> crate::Result<Self> {
+        let store =
+            Store::load_inline().map_err(|err| crate::Error::LicenseStore(Box::new(err)))?;
+        Ok(Detector { store })
+    }
+
+    /// Identify the license in `path`. Files that can't be read as UTF-8 or
+    /// don't closely match any known license yield `None`.
+    pub fn detect(&self, path: &Utf8Path) -> Option<Detected> {
+        let text = fs::read_to_string(path).ok()?;
+
+        let found = self.store.analyze(&TextData::new(&text));
+        if found.score < MIN_CONFIDENCE {
+            return None;
+        }
+
+        spdx::license_id(found.name).map(|license| Detected {
+            license,
+            confidence: (found.score * 100.0).round() as u8,
+        })
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::iter;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::{DepTable, Error, Licenses};
+
+/// A BOM written with `--format json`, reduced to what's compared.
+#[derive(Debug, Deserialize)]
+struct Snapshot {
+    bom_version: u32,
+    dependencies: Vec<SnapshotDependency>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SnapshotDependency {
+    name: String,
+    version: String,
+    #[serde(default)]
+    other_versions: Vec<String>,
+    license_expression: Option<String>,
+    license_source: String,
+}
+
+/// A crate in one version and its license, either the declared expression
+/// or where the license information would have come from.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Entry {
+    pub name: String,
+    pub version: String,
+    pub license: String,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ({})", self.name, self.version, self.license)
+    }
+}
+
+impl SnapshotDependency {
+    /// An entry for each version, including the ones folded into this one
+    /// with `--dedup-strategy name`
+    fn into_entries(self) -> impl Iterator<Item = Entry> {
+        let name = self.name;
+        let license = self.license_expression.unwrap_or(self.license_source);
+        iter::once(self.version)
+            .chain(self.other_versions)
+            .map(move |version| Entry {
+                name: name.clone(),
+                version,
+                license: license.clone(),
+            })
+    }
+}
+
+/// An entry for each version of a dependency, as versions folded into it
+/// with `--dedup-strategy name` are still in the tree.
+fn entries<'a>(dep: &'a DepTable<'_>) -> impl Iterator<Item = Entry> + 'a {
+    // Same as `license_expression` and `license_source` in JSON output
+    let license = match dep.licenses {
+        Licenses::List(ref expression) => expression.0.as_ref(),
+        Licenses::Invalid(license) => license,
+        Licenses::File(..) => "file",
+        Licenses::Missing => "missing",
+    };
+
+    iter::once(dep.version.clone())
+        .chain(dep.other_versions.iter().map(ToString::to_string))
+        .map(move |version| Entry {
+            name: dep.name.clone(),
+            version,
+            license: license.to_string(),
+        })
+}
+
+/// Differences between a snapshot and the current dependencies.
+#[derive(Debug, Default)]
+pub struct Diff {
+    pub added: Vec<Entry>,
+    pub removed: Vec<Entry>,
+    /// Crates whose only version changed, or whose license changed, as the
+    /// old and the new entry
+    pub changed: Vec<(Entry, Entry)>,
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.added.len() + self.removed.len() + self.changed.len()
+    }
+}
+
+/// Read a snapshot written earlier with `--format json`.
+pub fn read_snapshot(path: &Path) -> crate::Result<Vec<Entry>> {
+    let text = fs::read_to_string(path).map_err(|source| Error::ReadSnapshot {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let snapshot: Snapshot =
+        serde_json::from_str(&text).map_err(|source| Error::InvalidSnapshot {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+    if snapshot.bom_version != crate::json::BOM_VERSION {
+        return Err(Error::SnapshotVersion {
+            path: path.to_path_buf(),
+            found: snapshot.bom_version,
+            expected: crate::json::BOM_VERSION,
+        });
+    }
+
+    Ok(snapshot
+        .dependencies
+        .into_iter()
+        .flat_map(SnapshotDependency::into_entries)
+        .collect())
+}
+
+/// Compare the crates of a snapshot with the current dependencies. A crate
+/// going from one version to another is a change, while a crate gaining or
+/// losing one of several versions is an addition or removal.
+pub fn diff(old: Vec<Entry>, new: &[DepTable<'_>]) -> Diff {
+    let mut crates: BTreeMap<String, (Vec<Entry>, Vec<Entry>)> = BTreeMap::new();
+    for entry in old {
+        crates.entry(entry.name.clone()).or_default().0.push(entry);
+    }
+    for entry in new.iter().flat_map(entries) {
+        crates.entry(entry.name.clone()).or_default().1.push(entry);
+    }
+
+    let mut diff = Diff::default();
+    for (_, (mut old, mut new)) in crates {
+        // Versions present on both sides can only change their license
+        old.retain(|old_entry| {
+            let Some(i) = new.iter().position(|e| e.version == old_entry.version) else {
+                return true;
+            };
+            let new_entry = new.remove(i);
+            if new_entry.license != old_entry.license {
+                diff.changed.push((old_entry.clone(), new_entry));
+            }
+            false
+        });
+
+        if old.len() == 1 && new.len() == 1 {
+            diff.changed.push((old.remove(0), new.remove(0)));
+        }
+        diff.removed.append(&mut old);
+        diff.added.append(&mut new);
+    }
+
+    diff.added.sort();
+    diff.removed.sort();
+    diff
+}
+
+pub fn write(out: &mut impl Write, diff: &Diff) -> io::Result<()> {
+    if diff.is_empty() {
+        return writeln!(out, "No changes since the snapshot");
+    }
+
+    if !diff.added.is_empty() {
+        writeln!(out, "Added:")?;
+        for entry in &diff.added {
+            writeln!(out, "  + {entry}")?;
+        }
+    }
+
+    if !diff.removed.is_empty() {
+        writeln!(out, "Removed:")?;
+        for entry in &diff.removed {
+            writeln!(out, "  - {entry}")?;
+        }
+    }
+
+    if !diff.changed.is_empty() {
+        writeln!(out, "Changed:")?;
+        for (old, new) in &diff.changed {
+            let mut change = format!("  ~ {} ", old.name);
+            if old.version == new.version {
+                change.push_str(&old.version);
+            } else {
+                change.push_str(&format!("{} -> {}", old.version, new.version));
+            }
+            if old.license != new.license {
+                change.push_str(&format!(": {} -> {}", old.license, new.license));
+            }
+            writeln!(out, "{change}")?;
+        }
+    }
+
+    Ok(())
+}
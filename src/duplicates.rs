@@ -0,0 +1,36 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::{DepTable, TableStyle};
+
+/// List the crates that appear in more than one version along with the
+/// licenses of each version.
+pub fn write(out: &mut impl Write, list: &[DepTable<'_>], style: TableStyle) -> io::Result<()> {
+    let mut crates: BTreeMap<&str, Vec<&DepTable>> = BTreeMap::new();
+    for dep in list {
+        crates.entry(&dep.name).or_default().push(dep);
+    }
+    crates.retain(|_, versions| versions.len() > 1);
+
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record(["Name", "Version", "Licenses"]);
+    for versions in crates.values_mut() {
+        versions.sort_by(|a, b| a.package.version.cmp(&b.package.version));
+        for dep in versions {
+            builder.push_record([
+                dep.name.clone(),
+                dep.version.clone(),
+                dep.licenses.to_string(),
+            ]);
+        }
+    }
+
+    writeln!(out, "{}", crate::render_table(builder, style))?;
+    writeln!(
+        out,
+        "\n{} crates appear in more than one version",
+        crates.len()
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use std::io;
+use std::path::PathBuf;
+
+/// Why a bill of materials couldn't be built, so that callers can tell
+/// problems with the workspace or its configuration from failing I/O.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// `cargo metadata` was run without resolving the dependencies, as with
+    /// `--no-deps`
+    #[error("cargo metadata did not return a dependency graph")]
+    NoDependencyGraph,
+    /// A package to list the dependencies of isn't a workspace member
+    #[error("package `{0}` is not a member of the workspace")]
+    NotAMember(String),
+    /// The directory of a package couldn't be scanned for license files
+    #[error("failed to read the package directory of {name} {version}")]
+    PackageDir {
+        name: String,
+        version: String,
+        #[source]
+        source: io::Error,
+    },
+    /// The license texts for `--detect-licenses` couldn't be loaded
+    #[error("failed to load SPDX license texts")]
+    LicenseStore(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// The `[workspace.metadata.cargo-bom]` table of `Cargo.toml` has
+    /// unknown keys or values of the wrong type
+    #[error("invalid [workspace.metadata.cargo-bom] table in Cargo.toml")]
+    InvalidConfig(#[source] serde_json::Error),
+    /// A license in `allow` or `deny` of the configuration isn't a valid
+    /// SPDX license
+    #[error("invalid license {license:?} in `{key}` of [workspace.metadata.cargo-bom]")]
+    InvalidConfigLicense {
+        license: String,
+        key: &'static str,
+        #[source]
+        source: spdx::error::ParseError,
+    },
+    /// The snapshot for `--diff` couldn't be read
+    #[error("failed to read snapshot {}", .path.display())]
+    ReadSnapshot {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// The snapshot for `--diff` isn't `json` output
+    #[error("{} isn't a BOM written with --format json", .path.display())]
+    InvalidSnapshot {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// The snapshot for `--diff` was written by a version of cargo-bom with
+    /// a different JSON format
+    #[error("snapshot {} has version {found} of the JSON format, expected {expected}", .path.display())]
+    SnapshotVersion {
+        path: PathBuf,
+        found: u32,
+        expected: u32,
+    },
+    /// A directory for `--license-dir` couldn't be created
+    #[error("failed to create directory {}", .path.display())]
+    CreateDir {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// A file couldn't be written
+    #[error("failed to write {}", .path.display())]
+    WriteFile {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// The `--template` isn't a valid Handlebars template
+    #[error("invalid template")]
+    InvalidTemplate(#[source] Box<handlebars::TemplateError>),
+    /// The template refers to fields that don't exist or writing it failed
+    #[error("failed to render the template")]
+    RenderTemplate(#[source] Box<handlebars::RenderError>),
+    /// Writing the output failed
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
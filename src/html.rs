@@ -0,0 +1,131 @@
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+
+use itertools::Itertools;
+
+use crate::{
+    dedup_license_texts, read_license_texts, Column, DepTable, LicenseTable, LicenseTextOptions,
+};
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; vertical-align: top; }
+th { background: #eee; cursor: pointer; user-select: none; }
+details { margin: 0.5em 0; }
+pre { background: #f6f6f6; padding: 0.5em; overflow-x: auto; }";
+
+/// Sort the rows by the text of a column when its header is clicked,
+/// reversing the order on a second click.
+const SCRIPT: &str = "\
+document.querySelectorAll('table.bom th').forEach((th, column) => {
+  th.addEventListener('click', () => {
+    const tbody = th.closest('table').tBodies[0];
+    const ascending = th.dataset.order !== 'asc';
+    th.dataset.order = ascending ? 'asc' : 'desc';
+    const cell = (row) => row.cells[column].textContent;
+    const rows = Array.from(tbody.rows).sort((a, b) =>
+      cell(a).localeCompare(cell(b), undefined, { numeric: true }));
+    if (!ascending) rows.reverse();
+    rows.forEach((row) => tbody.appendChild(row));
+  });
+});";
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn write_texts<'a>(
+    out: &mut impl Write,
+    summary: &str,
+    texts: impl IntoIterator<Item = &'a Vec<u8>>,
+) -> io::Result<()> {
+    writeln!(out, "<details>")?;
+    writeln!(out, "<summary>{}</summary>", escape(summary))?;
+    for buf in texts {
+        writeln!(out, "<pre>{}</pre>", escape(&String::from_utf8_lossy(buf)))?;
+    }
+    writeln!(out, "</details>")
+}
+
+/// Write the dependencies as an HTML table followed by the license texts in
+/// collapsed `<details>` elements. With `standalone` the table is wrapped in
+/// a complete document with a style sheet and a script for sorting the
+/// columns, otherwise it's a fragment to embed into other pages.
+pub fn write(
+    out: &mut impl Write,
+    depencies_list: &[DepTable<'_>],
+    licenses_list: &BTreeSet<LicenseTable>,
+    columns: &[Column],
+    text_options: LicenseTextOptions,
+    standalone: bool,
+) -> io::Result<()> {
+    if standalone {
+        writeln!(out, "<!DOCTYPE html>")?;
+        writeln!(out, "<html lang=\"en\">")?;
+        writeln!(out, "<head>")?;
+        writeln!(out, "<meta charset=\"utf-8\">")?;
+        writeln!(out, "<title>Bill of Materials</title>")?;
+        writeln!(out, "<style>\n{STYLE}\n</style>")?;
+        writeln!(out, "</head>")?;
+        writeln!(out, "<body>")?;
+    }
+
+    writeln!(out, "<table class=\"bom\">")?;
+    let header = columns
+        .iter()
+        .map(|column| format!("<th>{}</th>", escape(column.header())))
+        .join("");
+    writeln!(out, "<thead><tr>{header}</tr></thead>")?;
+    writeln!(out, "<tbody>")?;
+    for dep in depencies_list {
+        let row = columns
+            .iter()
+            .map(|column| format!("<td>{}</td>", escape(&column.cell(dep))))
+            .join("");
+        writeln!(out, "<tr>{row}</tr>")?;
+    }
+    writeln!(out, "</tbody>")?;
+    writeln!(out, "</table>")?;
+
+    let license_texts = read_license_texts(licenses_list, text_options.max_bytes);
+
+    if text_options.dedup {
+        for (tables, buf) in dedup_license_texts(license_texts) {
+            let used_by = tables
+                .iter()
+                .map(|table| format!("{} {}", table.name, table.version))
+                .join(", ");
+            write_texts(out, &format!("License used by {used_by}"), &[buf])?;
+        }
+    } else {
+        for (LicenseTable { name, version, .. }, texts) in license_texts {
+            if !texts.is_empty() {
+                write_texts(
+                    out,
+                    &format!("{name} {version}"),
+                    texts.iter().map(|(_, buf)| buf),
+                )?;
+            }
+        }
+    }
+
+    if standalone {
+        writeln!(out, "<script>\n{SCRIPT}\n</script>")?;
+        writeln!(out, "</body>")?;
+        writeln!(out, "</html>")?;
+    }
+
+    Ok(())
+}
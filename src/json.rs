@@ -0,0 +1,195 @@
+use std::collections::{BTreeSet, HashMap};
+use std::io::{self, Write};
+
+use base64::Engine;
+use serde::Serialize;
+
+use crate::{read_license_texts, DepTable, LicenseTable, Licenses};
+
+/// Version of the document format described by `schema/bom.schema.json`.
+/// Bumped whenever a field is removed, renamed or changes its meaning.
+pub(crate) const BOM_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+struct Bom<'a> {
+    bom_version: u32,
+    dependencies: Vec<Dependency<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Dependency<'a> {
+    name: &'a str,
+    version: &'a str,
+    /// Versions sharing the entry with `--dedup-strategy name`
+    other_versions: Vec<String>,
+    licenses: Vec<String>,
+    license_expression: Option<&'a str>,
+    license_source: LicenseSource,
+    license_file: Option<&'a str>,
+    /// License detected from the license file with `--detect-licenses`
+    detected_license: Option<&'static str>,
+    detection_confidence: Option<u8>,
+    kinds: Vec<String>,
+    direct: bool,
+    workspace_member: bool,
+    optional: bool,
+    repository: Option<&'a str>,
+    authors: &'a [String],
+    description: Option<&'a str>,
+    source: Option<String>,
+    git_revision: Option<&'a str>,
+    features: &'a [String],
+    /// Contents of the license files with `--embed-license-text`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    license_texts: Option<Vec<LicenseText>>,
+}
+
+#[derive(Debug, Serialize)]
+struct LicenseText {
+    /// Name of the file
+    file: String,
+    /// Contents of the file, base64-encoded
+    content: String,
+}
+
+/// License files to embed into the entries.
+#[derive(Debug, Clone, Copy)]
+pub struct Embed<'a> {
+    pub licenses: &'a BTreeSet<LicenseTable>,
+    /// Cut off files longer than this many bytes
+    pub max_bytes: Option<u64>,
+}
+
+/// Read the license files to embed, by crate name and version.
+fn embedded_texts(embed: Embed<'_>) -> HashMap<(&str, &str), Vec<LicenseText>> {
+    let engine = base64::engine::general_purpose::STANDARD;
+    read_license_texts(embed.licenses, embed.max_bytes)
+        .into_iter()
+        .map(|(table, files)| {
+            let texts = files
+                .into_iter()
+                .map(|(file, buf)| LicenseText {
+                    file: file.file_name().unwrap_or(file.as_str()).to_string(),
+                    content: engine.encode(buf),
+                })
+                .collect();
+            ((table.name.as_str(), table.version.as_str()), texts)
+        })
+        .collect()
+}
+
+/// Entries of the dependencies, with their license texts when embedding
+/// them.
+fn dependencies<'a>(list: &'a [DepTable<'a>], embed: Option<Embed<'_>>) -> Vec<Dependency<'a>> {
+    let mut texts = embed.map(embedded_texts);
+    list.iter()
+        .map(|dep| Dependency {
+            license_texts: texts.as_mut().map(|texts| {
+                texts
+                    .remove(&(dep.name.as_str(), dep.version.as_str()))
+                    .unwrap_or_default()
+            }),
+            ..Dependency::from(dep)
+        })
+        .collect()
+}
+
+/// Where the license information of a dependency came from. Lets consumers
+/// tell a declared "MIT" apart from a crate that only ships a license file.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum LicenseSource {
+    Declared,
+    /// Declared license isn't a valid SPDX expression
+    Invalid,
+    File,
+    Missing,
+}
+
+impl<'a> From<&'a DepTable<'a>> for Dependency<'a> {
+    fn from(dep: &'a DepTable<'a>) -> Self {
+        let mut licenses = Vec::new();
+        let mut license_expression = None;
+        let mut license_file = None;
+        let mut detected_license = None;
+        let mut detection_confidence = None;
+
+        let license_source = match dep.licenses {
+            Licenses::List(ref expression) => {
+                licenses = expression
+                    .requirements()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect();
+                license_expression = Some(expression.0.as_ref());
+                LicenseSource::Declared
+            }
+            Licenses::Invalid(license) => {
+                license_expression = Some(license);
+                LicenseSource::Invalid
+            }
+            Licenses::File(ref file, ref detected) => {
+                license_file = Some(file.as_str());
+                if let Some(detected) = detected {
+                    detected_license = Some(detected.license.name);
+                    detection_confidence = Some(detected.confidence);
+                }
+                LicenseSource::File
+            }
+            Licenses::Missing => LicenseSource::Missing,
+        };
+
+        Dependency {
+            name: &dep.name,
+            version: &dep.version,
+            other_versions: dep.other_versions.iter().map(ToString::to_string).collect(),
+            licenses,
+            license_expression,
+            license_source,
+            license_file,
+            detected_license,
+            detection_confidence,
+            kinds: dep.kinds.0.iter().map(ToString::to_string).collect(),
+            direct: dep.direct,
+            workspace_member: dep.member,
+            optional: dep.optional,
+            repository: dep.package.repository.as_deref(),
+            authors: &dep.authors,
+            description: dep.package.description.as_deref(),
+            source: dep.package.source.as_ref().map(ToString::to_string),
+            git_revision: dep.git_revision(),
+            features: dep.features,
+            license_texts: None,
+        }
+    }
+}
+
+pub fn write(
+    out: &mut impl Write,
+    list: &[DepTable<'_>],
+    embed: Option<Embed<'_>>,
+) -> io::Result<()> {
+    let bom = Bom {
+        bom_version: BOM_VERSION,
+        dependencies: dependencies(list, embed),
+    };
+
+    serde_json::to_writer_pretty(&mut *out, &bom)?;
+    out.write_all(b"\n")
+}
+
+/// Write each dependency as a JSON object on a line of its own, the same as
+/// the entries of the `dependencies` array, so consumers can process the
+/// BOM as a stream. Each line is flushed as soon as it's written.
+pub fn write_lines(
+    out: &mut impl Write,
+    list: &[DepTable<'_>],
+    embed: Option<Embed<'_>>,
+) -> io::Result<()> {
+    for dep in dependencies(list, embed) {
+        serde_json::to_writer(&mut *out, &dep)?;
+        out.write_all(b"\n")?;
+        out.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,1505 @@
+//! Collect the dependencies of a Cargo workspace along with their licenses
+//! and render them as a bill of materials.
+//!
+//! [`build_bom`] gathers the data from `cargo metadata` output; the
+//! renderers in the submodules, [`write_table`] and [`write_license_texts`]
+//! turn it into the various output formats of `cargo bom`.
+//!
+//! Failures are reported as an [`Error`] telling what went wrong, so callers
+//! can react to a broken workspace differently than to a policy violation.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+
+use cargo_metadata::{camino, DependencyKind, Metadata, Package, PackageId};
+use clap::ValueEnum;
+use itertools::Itertools;
+use rayon::prelude::*;
+
+pub mod attribution;
+pub mod compatibility;
+pub mod config;
+pub mod copyleft;
+pub mod csv;
+pub mod cyclonedx;
+pub mod detect;
+pub mod diff;
+pub mod duplicates;
+mod error;
+pub mod html;
+pub mod json;
+pub mod license_dir;
+pub mod markdown;
+pub mod members;
+pub mod ort;
+pub mod policy;
+mod registries;
+mod resolve;
+pub mod spdx_doc;
+pub mod summary;
+pub mod template;
+pub mod toml;
+pub mod tsv;
+mod yanked;
+
+pub use error::{Error, Result};
+pub use resolve::Platform;
+
+/// Order of the dependencies in the bill of materials.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortBy {
+    /// Crate name
+    #[default]
+    Name,
+    /// Crate version, ordered by semantic versioning
+    Version,
+    /// Licenses of the crate
+    License,
+    /// Size of the crate's source on disk, largest first
+    Size,
+}
+
+/// What makes two dependencies the same crate in the bill of materials.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DedupStrategy {
+    /// List each version of a crate separately
+    #[default]
+    NameVersion,
+    /// List the versions of a crate in one row, unless their licenses
+    /// differ
+    Name,
+}
+
+/// How email addresses in the authors of a crate are shown.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AuthorEmails {
+    /// Show the authors as the crate lists them
+    #[default]
+    Keep,
+    /// Leave out email addresses, such as in `Name <name@example.com>`
+    Strip,
+}
+
+impl AuthorEmails {
+    fn apply(self, authors: &[String]) -> Vec<String> {
+        match self {
+            AuthorEmails::Keep => authors.to_vec(),
+            AuthorEmails::Strip => authors
+                .iter()
+                .map(|author| strip_email(author))
+                .filter(|author| !author.is_empty())
+                .collect(),
+        }
+    }
+}
+
+/// Remove the `<...>` parts of an author, leaving nothing of one that is
+/// only an email address.
+fn strip_email(author: &str) -> String {
+    let mut name = String::new();
+    let mut rest = author;
+    while let Some(start) = rest.find('<') {
+        name.push_str(&rest[..start]);
+        rest = rest[start..]
+            .find('>')
+            .map_or("", |end| &rest[start + end + 1..]);
+    }
+    name.push_str(rest);
+
+    let name = name.split_whitespace().join(" ");
+    if name.contains('@') && !name.contains(' ') {
+        return String::new();
+    }
+    name
+}
+
+/// Border style of the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TableStyle {
+    /// Box-drawing characters
+    Modern,
+    /// Box-drawing characters with rounded corners
+    ModernRounded,
+    /// Box-drawing characters without horizontal lines between rows
+    Sharp,
+    /// Like sharp, with rounded corners
+    Rounded,
+    /// Double-line box-drawing characters
+    Extended,
+    /// Plain ASCII characters
+    Ascii,
+    /// Plain ASCII characters with rounded corners
+    AsciiRounded,
+    /// Dots and colons
+    Dots,
+    /// PostgreSQL psql style
+    Psql,
+    /// Markdown table
+    Markdown,
+    /// reStructuredText table
+    ReStructuredText,
+    /// No borders, columns separated by spaces
+    Blank,
+    /// No borders at all
+    Empty,
+}
+
+/// Markers around the license texts printed after the table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Delimiters {
+    /// `-----BEGIN name version LICENSES-----` and `-----END ...-----`
+    /// around the texts of each crate, separated by `-----NEXT LICENSE-----`
+    #[default]
+    Armored,
+    /// A `=== name version: file ===` line above each license text
+    Headings,
+}
+
+/// How the table of dependencies is printed.
+#[derive(Debug, Clone, Copy)]
+pub struct TableOptions<'a> {
+    pub style: TableStyle,
+    pub columns: &'a [Column],
+    /// Highlight crates with missing or unclear licenses
+    pub color: bool,
+    /// Maximum number of characters in the description column, 0 for no
+    /// limit
+    pub description_width: usize,
+    /// Width to wrap the table to
+    pub max_width: Option<usize>,
+}
+
+/// How the license texts after the table are printed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LicenseTextOptions {
+    /// Print each distinct text only once, listing the crates using it
+    pub dedup: bool,
+    pub delimiters: Delimiters,
+    /// Cut off license files longer than this many bytes
+    pub max_bytes: Option<u64>,
+}
+
+/// Kind of dependency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
+pub enum Kind {
+    /// Normal dependencies
+    Normal,
+    /// Development dependencies used by tests, examples and benchmarks
+    Dev,
+    /// Build dependencies used by build scripts
+    Build,
+    /// All of the above
+    All,
+}
+
+impl Kind {
+    /// Resolve the requested kinds, defaulting to normal dependencies.
+    pub fn expand(kinds: &[Kind]) -> BTreeSet<Kind> {
+        if kinds.is_empty() {
+            return BTreeSet::from([Kind::Normal]);
+        }
+
+        if kinds.contains(&Kind::All) {
+            return BTreeSet::from([Kind::Normal, Kind::Dev, Kind::Build]);
+        }
+
+        kinds.iter().copied().collect()
+    }
+
+    fn from_dependency_kind(kind: DependencyKind) -> Option<Kind> {
+        match kind {
+            DependencyKind::Normal => Some(Kind::Normal),
+            DependencyKind::Development => Some(Kind::Dev),
+            DependencyKind::Build => Some(Kind::Build),
+            DependencyKind::Unknown => None,
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            Kind::Normal => write!(f, "normal"),
+            Kind::Dev => write!(f, "dev"),
+            Kind::Build => write!(f, "build"),
+            Kind::All => write!(f, "all"),
+        }
+    }
+}
+
+/// The kinds of dependency through which a crate was pulled in.
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Kinds(pub BTreeSet<Kind>);
+
+impl fmt::Display for Kinds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{}", self.0.iter().join(", "))
+    }
+}
+
+/// What to include in the bill of materials.
+#[derive(Debug, Default)]
+pub struct BomOptions {
+    /// Workspace packages to list the dependencies of, all members if empty
+    pub packages: Vec<String>,
+    /// Kinds of dependencies to include, normal dependencies if empty
+    pub kinds: Vec<Kind>,
+    /// Maximum depth of the dependency graph, unlimited if `None`
+    pub depth: Option<u32>,
+    /// Crates to leave out by name
+    pub exclude: Vec<glob::Pattern>,
+    /// Only follow the dependencies active on the platform, all of them if
+    /// `None`
+    pub platform: Option<Platform>,
+    /// Leave out crates all of whose licenses are satisfied by one of these
+    pub hide_licenses: Vec<spdx::Licensee>,
+    /// Identify licenses from license files when there's no `license` field
+    pub detect_licenses: bool,
+    /// List the workspace members themselves too
+    pub include_workspace_members: bool,
+    /// List path dependencies from outside of the workspace
+    pub include_path_dependencies: bool,
+    /// Measure the size of each crate's source on disk, always done when
+    /// sorting by size
+    pub sizes: bool,
+    /// Look up whether crates.io dependencies have been yanked
+    pub check_yanked: bool,
+    pub sort_by: SortBy,
+    /// Whether versions of a crate are listed separately
+    pub dedup: DedupStrategy,
+    /// Reverse the order given by `sort_by`
+    pub reverse: bool,
+    pub author_emails: AuthorEmails,
+    /// Show a progress bar on stderr while scanning the package directories
+    pub progress: bool,
+    pub license_files: LicenseFileOptions,
+}
+
+/// Dependencies of a workspace and their license files.
+#[derive(Debug)]
+pub struct Bom<'a> {
+    /// Dependencies in the order given by [`BomOptions::sort_by`] and
+    /// [`BomOptions::reverse`]
+    pub dependencies: Vec<DepTable<'a>>,
+    pub licenses: BTreeSet<LicenseTable>,
+}
+
+/// Collect the dependencies of the workspace described by `metadata`.
+pub fn build_bom<'a>(metadata: &'a Metadata, opts: &BomOptions) -> Result<Bom<'a>> {
+    let dependencies = resolve_dependencies(metadata, opts)?;
+
+    let registries = registries::Registries::load(&metadata.workspace_root);
+    let index = opts.check_yanked.then(yanked::Index::open);
+
+    let detector = opts
+        .detect_licenses
+        .then(detect::Detector::new)
+        .transpose()?;
+
+    let mut depencies_list = BTreeSet::new();
+    let mut licenses_list = BTreeSet::new();
+
+    // Scanning package directories for license files is the slow part on
+    // large dependency trees, so do it in parallel. Results are collected in
+    // order and end up in sorted sets anyway.
+    let sizes = opts.sizes || opts.sort_by == SortBy::Size;
+    let progress = if opts.progress {
+        indicatif::ProgressBar::new(dependencies.len() as u64).with_style(
+            indicatif::ProgressStyle::with_template("{bar:40} {pos}/{len} {msg}")
+                .expect("valid progress bar template"),
+        )
+    } else {
+        indicatif::ProgressBar::hidden()
+    };
+    progress.set_message("scanning license files");
+    let scanned = dependencies
+        .par_iter()
+        .map(|resolved| {
+            let package = resolved.package;
+            let scan = || -> io::Result<_> {
+                let mut warnings = Vec::new();
+                let license_files =
+                    package_license_files(package, &opts.license_files, &mut warnings)?;
+                let size = if sizes {
+                    Some(package_size(package)?)
+                } else {
+                    None
+                };
+                Ok((license_files, size, warnings))
+            };
+            let scanned = scan().map_err(|source| Error::PackageDir {
+                name: package.name.clone(),
+                version: package.version.to_string(),
+                source,
+            });
+            progress.inc(1);
+            scanned
+        })
+        .collect::<Result<Vec<_>>>()?;
+    progress.finish_and_clear();
+
+    // Printed once the progress bar is gone, so they don't break it up
+    for (_, _, warnings) in &scanned {
+        for warning in warnings {
+            eprintln!("warning: {warning}");
+        }
+    }
+
+    for (resolved, (license_files, size, _)) in dependencies.into_iter().zip(scanned) {
+        let dep = resolved.package;
+        let name = dep.name.clone();
+        let version = dep.version.to_string();
+        let mut licenses = package_licenses(dep);
+
+        if let (Some(ref detector), Licenses::File(_, ref mut detected)) =
+            (&detector, &mut licenses)
+        {
+            if let Ok(Some(file)) = declared_license_file(dep) {
+                *detected = detector.detect(&file);
+            }
+        }
+
+        if is_hidden(&licenses, &opts.hide_licenses) {
+            continue;
+        }
+
+        depencies_list.insert(DepTable {
+            name: name.clone(),
+            version: version.clone(),
+            licenses,
+            kinds: resolved.kinds,
+            features: resolved.features,
+            authors: opts.author_emails.apply(&dep.authors),
+            direct: resolved.direct,
+            member: resolved.member,
+            optional: resolved.optional,
+            registry: dep
+                .source
+                .as_ref()
+                .and_then(|source| registries.name(source))
+                .map(ToString::to_string),
+            size,
+            yanked: index.as_ref().and_then(|index| index.is_yanked(dep)),
+            other_versions: Vec::new(),
+            package: dep,
+        });
+
+        licenses_list.insert(LicenseTable {
+            name,
+            version,
+            license_files,
+        });
+    }
+
+    let mut depencies_list: Vec<DepTable> = depencies_list.into_iter().collect();
+    if opts.dedup == DedupStrategy::Name {
+        collapse_versions(&mut depencies_list);
+    }
+    sort_dependencies(&mut depencies_list, opts);
+
+    Ok(Bom {
+        dependencies: depencies_list,
+        licenses: licenses_list,
+    })
+}
+
+/// Combine the BOMs of several workspaces into one. A crate depended on by
+/// more than one of them is listed once, with the kinds of dependency of
+/// all of them.
+pub fn merge_boms<'a>(boms: Vec<Bom<'a>>, opts: &BomOptions) -> Bom<'a> {
+    let mut depencies_list = Vec::new();
+    let mut licenses_list = BTreeSet::new();
+    for bom in boms {
+        depencies_list.extend(bom.dependencies);
+        licenses_list.extend(bom.licenses);
+    }
+
+    depencies_list.sort();
+    depencies_list.dedup_by(|dup, dep| {
+        if dup != dep {
+            return false;
+        }
+        dep.kinds.0.extend(dup.kinds.0.iter().copied());
+        dep.direct |= dup.direct;
+        dep.member |= dup.member;
+        dep.optional &= dup.optional;
+        // The BOMs were collapsed each on their own, so one may have folded
+        // in versions the other doesn't have
+        dep.other_versions.append(&mut dup.other_versions);
+        true
+    });
+    if opts.dedup == DedupStrategy::Name {
+        collapse_versions(&mut depencies_list);
+    }
+    sort_dependencies(&mut depencies_list, opts);
+
+    Bom {
+        dependencies: depencies_list,
+        licenses: licenses_list,
+    }
+}
+
+/// Fold the versions of a crate, ordered by name, into the row of the newest
+/// one. Versions under different licenses keep rows of their own
+/// so that no license goes unlisted.
+fn collapse_versions(depencies_list: &mut Vec<DepTable<'_>>) {
+    let mut collapsed: Vec<DepTable> = Vec::with_capacity(depencies_list.len());
+    for mut dep in depencies_list.drain(..) {
+        let same = collapsed
+            .iter_mut()
+            .rev()
+            .take_while(|other| other.name == dep.name)
+            .find(|other| other.licenses == dep.licenses);
+        let Some(other) = same else {
+            collapsed.push(dep);
+            continue;
+        };
+
+        // Keep the newest version in front
+        if dep.package.version > other.package.version {
+            std::mem::swap(other, &mut dep);
+        }
+        other.other_versions.push(dep.package.version.clone());
+        other.other_versions.append(&mut dep.other_versions);
+        other.kinds.0.extend(dep.kinds.0);
+        other.direct |= dep.direct;
+        other.member |= dep.member;
+        other.optional &= dep.optional;
+    }
+    for dep in &mut collapsed {
+        dep.other_versions.sort();
+        dep.other_versions.dedup();
+    }
+    *depencies_list = collapsed;
+}
+
+/// Put the dependencies, ordered by name, in the order asked for.
+fn sort_dependencies(depencies_list: &mut [DepTable<'_>], opts: &BomOptions) {
+    // The sort is stable, so crates with the same version or license stay
+    // ordered by name
+    match opts.sort_by {
+        SortBy::Name => {}
+        SortBy::Version => depencies_list.sort_by(|a, b| a.package.version.cmp(&b.package.version)),
+        SortBy::License => depencies_list.sort_by_cached_key(|dep| dep.licenses.to_string()),
+        SortBy::Size => depencies_list.sort_by_key(|dep| std::cmp::Reverse(dep.size)),
+    }
+    if opts.reverse {
+        depencies_list.reverse();
+    }
+}
+
+/// Whether every license a crate may be used under is hidden. A crate with
+/// `MIT OR GPL-3.0` is still shown when only `MIT` is hidden, as the choice
+/// between them may be worth a look.
+fn is_hidden(licenses: &Licenses<'_>, hidden: &[spdx::Licensee]) -> bool {
+    let hides = |req: &spdx::LicenseReq| hidden.iter().any(|l| l.satisfies(req));
+    match *licenses {
+        _ if hidden.is_empty() => false,
+        Licenses::List(ref expression) => expression.0.requirements().all(|r| hides(&r.req)),
+        Licenses::File(_, Some(ref detected)) => hides(&detected.license.into()),
+        Licenses::Invalid(_) | Licenses::File(_, None) | Licenses::Missing => false,
+    }
+}
+
+/// Walk the dependency graph and leave out the excluded crates and, unless
+/// asked for, path dependencies.
+fn resolve_dependencies<'a>(
+    metadata: &'a Metadata,
+    opts: &BomOptions,
+) -> Result<Vec<resolve::Resolved<'a>>> {
+    let roots = workspace_roots(metadata, &opts.packages)?;
+    let kinds = Kind::expand(&opts.kinds);
+
+    let dependencies = resolve::dependencies(
+        metadata,
+        &roots,
+        &kinds,
+        opts.depth,
+        opts.include_workspace_members,
+        opts.platform.as_ref(),
+    )?;
+
+    Ok(dependencies
+        .into_values()
+        .filter(|resolved| {
+            !opts
+                .exclude
+                .iter()
+                .any(|pattern| pattern.matches(&resolved.package.name))
+        })
+        // Workspace members have no source either
+        .filter(|resolved| {
+            opts.include_path_dependencies || resolved.member || resolved.package.source.is_some()
+        })
+        .collect())
+}
+
+/// Number of dependencies in the bill of materials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Counts {
+    /// Crates counting each version separately
+    pub total: usize,
+    /// Crates with different names
+    pub distinct: usize,
+}
+
+/// Count the dependencies [`build_bom`] would list without looking at their
+/// licenses.
+pub fn count_dependencies(metadata: &Metadata, opts: &BomOptions) -> Result<Counts> {
+    count_merged_dependencies(std::slice::from_ref(metadata), opts)
+}
+
+/// Count the dependencies of several workspaces like [`merge_boms`] would
+/// list them, each crate only once.
+pub fn count_merged_dependencies(metadatas: &[Metadata], opts: &BomOptions) -> Result<Counts> {
+    let mut packages = BTreeSet::new();
+    for metadata in metadatas {
+        for resolved in resolve_dependencies(metadata, opts)? {
+            packages.insert((&resolved.package.name, &resolved.package.id));
+        }
+    }
+
+    Ok(Counts {
+        total: packages.len(),
+        distinct: packages.iter().map(|(name, _)| name).unique().count(),
+    })
+}
+
+/// Package URL identifying a crate. Crates from registries other than
+/// crates.io carry the registry in a `repository_url` qualifier and crates
+/// from git repositories the repository and commit in `vcs_url`, so the URL
+/// can be resolved back to the source.
+pub fn purl(package: &Package) -> String {
+    let purl = format!("pkg:cargo/{}@{}", package.name, package.version);
+
+    let Some(ref source) = package.source else {
+        return purl;
+    };
+    if source.is_crates_io() {
+        return purl;
+    }
+
+    let repr = source.repr.as_str();
+    if let Some(index) = repr
+        .strip_prefix("registry+")
+        .or_else(|| repr.strip_prefix("sparse+"))
+    {
+        return format!("{purl}?repository_url={}", purl_encode(index));
+    }
+
+    if let Some(repository) = repr.strip_prefix("git+") {
+        // `git+<url>?<ref>#<commit>` becomes `git+<url>@<commit>`
+        let (url, commit) = repository.rsplit_once('#').unwrap_or((repository, ""));
+        let url = url.split_once('?').map_or(url, |(url, _)| url);
+        let vcs_url = if commit.is_empty() {
+            format!("git+{url}")
+        } else {
+            format!("git+{url}@{commit}")
+        };
+        return format!("{purl}?vcs_url={}", purl_encode(&vcs_url));
+    }
+
+    purl
+}
+
+/// Percent-encode a qualifier value of a package URL. `:` and `/` are left
+/// as they are to keep URLs readable, as the spec allows.
+fn purl_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for b in value.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b':' | b'/' => {
+                encoded.push(b as char)
+            }
+            _ => encoded.push_str(&format!("%{b:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Find the workspace members to list the dependencies of. All members are
+/// used when no package names are given. Names of packages in another of
+/// several merged workspaces are skipped, as long as one of them is a member
+/// of this one.
+fn workspace_roots<'a>(
+    metadata: &'a cargo_metadata::Metadata,
+    names: &[String],
+) -> Result<Vec<&'a PackageId>> {
+    if names.is_empty() {
+        return Ok(metadata.workspace_members.iter().collect());
+    }
+
+    let roots: Vec<_> = names
+        .iter()
+        .filter_map(|name| {
+            metadata
+                .workspace_members
+                .iter()
+                .find(|id| metadata[id].name == *name)
+        })
+        .collect();
+    if roots.is_empty() {
+        return Err(Error::NotAMember(names[0].clone()));
+    }
+    Ok(roots)
+}
+
+/// Highlight crates whose licenses need a closer look.
+fn license_color(licenses: &Licenses<'_>) -> Option<tabled::settings::Color> {
+    use tabled::settings::Color;
+
+    match licenses {
+        Licenses::List(_) => None,
+        Licenses::File(_, Some(_)) => None,
+        Licenses::Invalid(_) | Licenses::File(_, None) => Some(Color::FG_YELLOW),
+        Licenses::Missing => Some(Color::FG_RED),
+    }
+}
+
+fn make_table(list: &[DepTable<'_>], options: &TableOptions<'_>) -> String {
+    use tabled::settings::object::Rows;
+
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record(options.columns.iter().map(|column| column.header()));
+    for dep in list {
+        builder.push_record(options.columns.iter().map(|column| match column {
+            Column::Description => truncate(column.cell(dep), options.description_width),
+            Column::Version if dep.yanked == Some(true) => format!("{} (yanked)", column.cell(dep)),
+            _ => column.cell(dep),
+        }));
+    }
+
+    let mut table = builder.build();
+    if options.color {
+        for (i, dep) in list.iter().enumerate() {
+            if let Some(color) = license_color(&dep.licenses) {
+                // Row 0 is the header
+                table.modify(Rows::single(i + 1), color);
+            }
+        }
+    }
+
+    style_table(table, options.style, options.max_width)
+}
+
+/// Shorten `text` to at most `width` characters, ending it with an ellipsis
+/// when something was cut off. A width of 0 leaves the text as is.
+fn truncate(text: String, width: usize) -> String {
+    if width == 0 || text.chars().count() <= width {
+        return text;
+    }
+
+    let mut truncated: String = text.chars().take(width - 1).collect();
+    truncated.truncate(truncated.trim_end().len());
+    truncated.push('…');
+    truncated
+}
+
+fn render_table(builder: tabled::builder::Builder, style: TableStyle) -> String {
+    style_table(builder.build(), style, None)
+}
+
+/// Apply the border style and, with `max_width`, wrap the widest columns
+/// first until the table fits.
+fn style_table(mut table: tabled::Table, style: TableStyle, max_width: Option<usize>) -> String {
+    use tabled::settings::{peaker::PriorityMax, Style, Width};
+
+    match style {
+        TableStyle::Modern => table.with(Style::modern()),
+        TableStyle::ModernRounded => table.with(Style::modern_rounded()),
+        TableStyle::Sharp => table.with(Style::sharp()),
+        TableStyle::Rounded => table.with(Style::rounded()),
+        TableStyle::Extended => table.with(Style::extended()),
+        TableStyle::Ascii => table.with(Style::ascii()),
+        TableStyle::AsciiRounded => table.with(Style::ascii_rounded()),
+        TableStyle::Dots => table.with(Style::dots()),
+        TableStyle::Psql => table.with(Style::psql()),
+        TableStyle::Markdown => table.with(Style::markdown()),
+        TableStyle::ReStructuredText => table.with(Style::re_structured_text()),
+        TableStyle::Blank => table.with(Style::blank()),
+        TableStyle::Empty => table.with(Style::empty()),
+    };
+    if let Some(max_width) = max_width {
+        table.with(
+            Width::wrap(max_width)
+                .keep_words(true)
+                .priority(PriorityMax),
+        );
+    }
+    table.to_string()
+}
+
+pub fn write_table(
+    out: &mut impl Write,
+    depencies_list: &[DepTable<'_>],
+    options: &TableOptions<'_>,
+) -> io::Result<()> {
+    let table = make_table(depencies_list, options);
+
+    out.write_all(table.as_bytes())?;
+    out.write_all(b"\n")?;
+    out.flush()
+}
+
+/// Print the license texts that follow the table.
+pub fn write_license_texts(
+    out: &mut impl Write,
+    licenses_list: &BTreeSet<LicenseTable>,
+    text_options: LicenseTextOptions,
+) -> io::Result<()> {
+    let license_texts = read_license_texts(licenses_list, text_options.max_bytes);
+
+    if text_options.dedup {
+        for (tables, buf) in dedup_license_texts(license_texts) {
+            let used_by = tables
+                .iter()
+                .map(|table| format!("{} {}", table.name, table.version))
+                .join(", ");
+
+            match text_options.delimiters {
+                Delimiters::Armored => {
+                    writeln!(out, "\n-----BEGIN LICENSE-----")?;
+                    writeln!(out, "Used by: {used_by}\n")?;
+                    out.write_all(&buf)?;
+                    writeln!(out, "\n-----END LICENSE-----")?;
+                }
+                Delimiters::Headings => {
+                    writeln!(out, "\n=== {used_by} ===\n")?;
+                    write_text(out, &buf)?;
+                }
+            }
+            out.flush()?;
+        }
+
+        return Ok(());
+    }
+
+    for (LicenseTable { name, version, .. }, texts) in license_texts {
+        if texts.is_empty() {
+            continue;
+        }
+
+        if text_options.delimiters == Delimiters::Headings {
+            for (file, buf) in texts {
+                let file = file.file_name().unwrap_or(file.as_str());
+                writeln!(out, "\n=== {name} {version}: {file} ===\n")?;
+                write_text(out, &buf)?;
+            }
+            out.flush()?;
+            continue;
+        }
+
+        writeln!(out, "\n-----BEGIN {name} {version} LICENSES-----")?;
+
+        for (i, (_, buf)) in texts.iter().enumerate() {
+            if i > 0 {
+                out.write_all(b"\n-----NEXT LICENSE-----\n")?;
+            }
+            out.write_all(buf)?;
+        }
+
+        writeln!(out, "\n-----END {name} {version} LICENSES-----")?;
+        out.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Write a license text, ending it with a newline if the file doesn't, so
+/// the next heading always starts on a line of its own.
+fn write_text(out: &mut impl Write, buf: &[u8]) -> io::Result<()> {
+    out.write_all(buf)?;
+    if !buf.ends_with(b"\n") {
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Contents of the license files of a crate along with their paths. Files
+/// that couldn't be read are missing, so the texts are always kept with the
+/// path they were read from rather than matched up with `license_files`.
+type LicenseTexts<'a> = Vec<(&'a camino::Utf8Path, Vec<u8>)>;
+
+/// Read the license files of all crates in parallel. The texts are returned
+/// in the order of `licenses_list`.
+///
+/// Files that can't be read are skipped with a warning rather than failing
+/// the whole BOM, as they may have gone away since the scan, e.g. by a
+/// concurrent `cargo clean`.
+fn read_license_texts(
+    licenses_list: &BTreeSet<LicenseTable>,
+    max_bytes: Option<u64>,
+) -> Vec<(&LicenseTable, LicenseTexts<'_>)> {
+    let list: Vec<&LicenseTable> = licenses_list.iter().collect();
+    list.into_par_iter()
+        .map(|table| {
+            let files = table
+                .license_files
+                .iter()
+                .filter_map(|file| match read_license_text(file, max_bytes) {
+                    Ok(buf) => Some((file.as_path(), buf)),
+                    Err(err) => {
+                        eprintln!(
+                            "warning: failed to read license file of {} {}: {file}: {err}",
+                            table.name, table.version
+                        );
+                        None
+                    }
+                })
+                .collect();
+            (table, files)
+        })
+        .collect()
+}
+
+/// Read a license file, cutting it off after `max_bytes` with a marker.
+/// Some crates bundle the notices of everything they vendor into one huge
+/// file.
+fn read_license_text(file: &camino::Utf8Path, max_bytes: Option<u64>) -> io::Result<Vec<u8>> {
+    let Some(max_bytes) = max_bytes else {
+        return fs::read(file).map(normalize_line_endings);
+    };
+
+    let mut buf = Vec::new();
+    fs::File::open(file)?
+        .take(max_bytes + 1)
+        .read_to_end(&mut buf)?;
+    if buf.len() as u64 > max_bytes {
+        buf.truncate(max_bytes as usize);
+        buf.extend_from_slice(b"\n[truncated]");
+    }
+    Ok(normalize_line_endings(buf))
+}
+
+/// Turn CRLF line endings into LF, so texts from files checked out on
+/// Windows don't mix line endings with the output around them and the same
+/// license reads the same on every platform.
+fn normalize_line_endings(buf: Vec<u8>) -> Vec<u8> {
+    if !buf.windows(2).any(|pair| pair == b"\r\n") {
+        return buf;
+    }
+
+    let mut normalized = Vec::with_capacity(buf.len());
+    let mut bytes = buf.iter().peekable();
+    while let Some(&b) = bytes.next() {
+        if b == b'\r' && bytes.peek() == Some(&&b'\n') {
+            continue;
+        }
+        normalized.push(b);
+    }
+    normalized
+}
+
+/// Group identical license texts together along with the crates shipping
+/// them. Texts are kept in the order they're first seen in.
+fn dedup_license_texts<'a>(
+    license_texts: Vec<(&'a LicenseTable, LicenseTexts<'_>)>,
+) -> Vec<(Vec<&'a LicenseTable>, Vec<u8>)> {
+    let mut result: Vec<(Vec<&LicenseTable>, Vec<u8>)> = Vec::new();
+    let mut index: HashMap<Vec<u8>, usize> = HashMap::new();
+
+    for (table, texts) in license_texts {
+        for (_, buf) in texts {
+            match index.get(&buf) {
+                Some(&i) => {
+                    // A crate may ship the same text twice, e.g. `LICENSE`
+                    // and `license_file` pointing to it
+                    let tables = &mut result[i].0;
+                    if tables.last() != Some(&table) {
+                        tables.push(table);
+                    }
+                }
+                None => {
+                    index.insert(buf.clone(), result.len());
+                    result.push((vec![table], buf));
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Prefixes of license file names, matched case-insensitively. Covers
+/// extensions and suffixes like `LICENSE.md` and `LICENSE-MIT`.
+static LICENCE_FILE_NAMES: &[&str] = &[
+    "LICENSE",
+    "LICENCE",
+    "UNLICENSE",
+    "COPYRIGHT",
+    "COPYING",
+    "NOTICE",
+];
+
+/// Which files in the directory of a package count as its license files,
+/// besides the declared `license_file`.
+#[derive(Debug, Default, Clone)]
+pub struct LicenseFileOptions {
+    /// Names to recognize on top of those starting with `LICENSE`,
+    /// `COPYING` and the like, matched regardless of case
+    pub extra_names: Vec<glob::Pattern>,
+    /// Names to skip even if they look like license files, such as bundled
+    /// notices of vendored code, matched regardless of case
+    pub exclude: Vec<glob::Pattern>,
+}
+
+impl LicenseFileOptions {
+    fn is_license_file(&self, file_name: &str) -> bool {
+        let upper = file_name.to_ascii_uppercase();
+        let options = glob::MatchOptions {
+            case_sensitive: false,
+            ..glob::MatchOptions::new()
+        };
+
+        if self
+            .exclude
+            .iter()
+            .any(|pattern| pattern.matches_with(file_name, options))
+        {
+            return false;
+        }
+
+        LICENCE_FILE_NAMES
+            .iter()
+            .any(|license_name| upper.starts_with(license_name))
+            || self
+                .extra_names
+                .iter()
+                .any(|pattern| pattern.matches_with(file_name, options))
+    }
+}
+
+#[derive(Debug)]
+pub struct DepTable<'a> {
+    pub name: String,
+    pub version: String,
+    pub licenses: Licenses<'a>,
+    pub kinds: Kinds,
+    /// Features enabled in the resolved dependency graph
+    pub features: &'a [String],
+    /// Authors of the crate, with their email addresses left out with
+    /// [`AuthorEmails::Strip`]
+    pub authors: Vec<String>,
+    /// Whether a workspace member depends on the crate directly
+    pub direct: bool,
+    /// Whether the crate is a member of the workspace
+    pub member: bool,
+    /// Whether the crate is only pulled in through optional dependencies
+    pub optional: bool,
+    /// Name of the alternative registry or source replacement the crate
+    /// comes from, as configured in `.cargo/config.toml`
+    pub registry: Option<String>,
+    /// Size of the source in bytes when measured
+    pub size: Option<u64>,
+    /// Whether the version has been yanked from crates.io, when checked and
+    /// known
+    pub yanked: Option<bool>,
+    /// Older versions folded into this one with [`DedupStrategy::Name`]
+    pub other_versions: Vec<cargo_metadata::semver::Version>,
+    pub package: &'a Package,
+}
+
+// Order by name and version. The package ID keeps crates with the same name
+// and version from different sources apart.
+impl<'a> DepTable<'a> {
+    /// Commit the crate was checked out at when it comes from a git
+    /// repository. Cargo records it in the fragment of the source, as in
+    /// `git+https://github.com/owner/repo?branch=main#<commit>`.
+    pub fn git_revision(&self) -> Option<&'a str> {
+        let source = self.package.source.as_ref()?;
+        let (_, revision) = source.repr.strip_prefix("git+")?.rsplit_once('#')?;
+        Some(revision)
+    }
+
+    fn sort_key(&self) -> (&str, &str, &PackageId) {
+        (&self.name, &self.version, &self.package.id)
+    }
+}
+
+impl<'a> PartialEq for DepTable<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.sort_key() == other.sort_key()
+    }
+}
+
+impl<'a> Eq for DepTable<'a> {}
+
+impl<'a> PartialOrd for DepTable<'a> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a> Ord for DepTable<'a> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+/// Columns of the dependency table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Column {
+    Name,
+    Version,
+    Licenses,
+    /// Kinds of dependency through which the crate was pulled in
+    Kind,
+    Repository,
+    /// Authors of the crate separated by `; `
+    Authors,
+    Description,
+    /// Where the crate comes from: `crates.io`, another registry, a git
+    /// repository or a local `path`
+    Source,
+    /// Enabled features
+    Features,
+    /// Commit of crates from git repositories
+    Revision,
+    /// Size of the crate's source on disk
+    Size,
+    /// Whether a workspace member depends on the crate directly
+    Direct,
+    /// Whether the crate is a member of the workspace
+    Workspace,
+    /// Whether the crate is only pulled in through optional dependencies
+    Optional,
+    /// Rust edition of the crate
+    Edition,
+    /// Minimum supported Rust version declared with `rust-version`
+    RustVersion,
+}
+
+impl Column {
+    pub fn header(self) -> &'static str {
+        match self {
+            Column::Name => "Name",
+            Column::Version => "Version",
+            Column::Licenses => "Licenses",
+            Column::Kind => "Kind",
+            Column::Repository => "Repository",
+            Column::Authors => "Authors",
+            Column::Description => "Description",
+            Column::Source => "Source",
+            Column::Features => "Features",
+            Column::Revision => "Revision",
+            Column::Size => "Size",
+            Column::Direct => "Direct",
+            Column::Workspace => "Workspace",
+            Column::Optional => "Optional",
+            Column::Edition => "Edition",
+            Column::RustVersion => "Rust Version",
+        }
+    }
+
+    pub fn cell(self, dep: &DepTable<'_>) -> String {
+        match self {
+            Column::Name => dep.name.clone(),
+            Column::Version => dep
+                .other_versions
+                .iter()
+                .map(ToString::to_string)
+                .chain([dep.version.clone()])
+                .join(", "),
+            Column::Licenses => dep.licenses.to_string(),
+            Column::Kind => dep.kinds.to_string(),
+            Column::Repository => dep.package.repository.clone().unwrap_or_default(),
+            Column::Authors => dep.authors.join("; "),
+            // Descriptions spanning several lines would break up the rows
+            Column::Description => dep
+                .package
+                .description
+                .as_deref()
+                .unwrap_or_default()
+                .split_whitespace()
+                .join(" "),
+            Column::Source => match dep.package.source {
+                Some(ref source) if source.is_crates_io() => "crates.io".to_string(),
+                Some(ref source) => match dep.registry {
+                    Some(ref registry) => registry.clone(),
+                    None => source.to_string(),
+                },
+                None => "path".to_string(),
+            },
+            Column::Features => dep.features.join(", "),
+            Column::Revision => dep.git_revision().unwrap_or_default().to_string(),
+            Column::Size => dep.size.map(format_size).unwrap_or_default(),
+            Column::Direct => if dep.direct { "yes" } else { "no" }.to_string(),
+            Column::Workspace => if dep.member { "yes" } else { "no" }.to_string(),
+            Column::Optional => if dep.optional { "yes" } else { "no" }.to_string(),
+            Column::Edition => dep.package.edition.to_string(),
+            Column::RustVersion => dep
+                .package
+                .rust_version
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Licenses<'a> {
+    List(Box<LicenseExpression>),
+    /// `license` field that isn't a valid SPDX expression
+    Invalid(&'a str),
+    /// `license_file` and the license detected from its text
+    File(String, Option<detect::Detected>),
+    Missing,
+}
+
+impl<'a> fmt::Display for Licenses<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            Licenses::File(_, Some(ref detected)) => write!(f, "{}", detected),
+            Licenses::File(_, None) => write!(f, "Specified in license file"),
+            Licenses::Missing => write!(f, "Missing"),
+            Licenses::Invalid(license) => write!(f, "{}", license),
+            Licenses::List(ref expression) => {
+                let lics = expression.requirements().iter().join(", ");
+                write!(f, "{}", lics)
+            }
+        }
+    }
+}
+
+/// Parsed SPDX license expression of a package.
+#[derive(Debug)]
+pub struct LicenseExpression(spdx::Expression);
+
+impl LicenseExpression {
+    /// Individual license requirements of the expression without the
+    /// operators joining them.
+    // Use BTreeSet to get alphabetical order and deduplication automatically.
+    pub fn requirements(&self) -> BTreeSet<&spdx::LicenseReq> {
+        self.0.requirements().map(|er| &er.req).collect()
+    }
+
+    /// The expression as valid SPDX: `AND` and `OR` instead of `/`, and the
+    /// proper identifiers in place of imprecise names.
+    pub fn canonical(&self) -> String {
+        match spdx::Expression::canonicalize(self.0.as_ref()) {
+            Ok(Some(canonical)) => canonical,
+            _ => self.0.as_ref().to_string(),
+        }
+    }
+}
+
+impl PartialEq for LicenseExpression {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_ref() == other.0.as_ref()
+    }
+}
+
+impl Eq for LicenseExpression {}
+
+impl PartialOrd for LicenseExpression {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LicenseExpression {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.as_ref().cmp(other.0.as_ref())
+    }
+}
+
+/// Deprecated SPDX identifiers and their current forms. Lax parsing already
+/// maps `GPL-2.0+` and the like to `-or-later`, these are the ones it keeps.
+static DEPRECATED_LICENSES: &[(&str, &str)] = &[
+    ("AGPL-1.0", "AGPL-1.0-only"),
+    ("AGPL-3.0", "AGPL-3.0-only"),
+    ("BSD-2-Clause-FreeBSD", "BSD-2-Clause"),
+    ("BSD-2-Clause-NetBSD", "BSD-2-Clause"),
+    ("GFDL-1.1", "GFDL-1.1-only"),
+    ("GFDL-1.2", "GFDL-1.2-only"),
+    ("GFDL-1.3", "GFDL-1.3-only"),
+    ("GPL-1.0", "GPL-1.0-only"),
+    ("GPL-2.0", "GPL-2.0-only"),
+    (
+        "GPL-2.0-with-autoconf-exception",
+        "GPL-2.0-only WITH Autoconf-exception-2.0",
+    ),
+    (
+        "GPL-2.0-with-bison-exception",
+        "GPL-2.0-only WITH Bison-exception-2.2",
+    ),
+    (
+        "GPL-2.0-with-classpath-exception",
+        "GPL-2.0-only WITH Classpath-exception-2.0",
+    ),
+    (
+        "GPL-2.0-with-font-exception",
+        "GPL-2.0-only WITH Font-exception-2.0",
+    ),
+    (
+        "GPL-2.0-with-GCC-exception",
+        "GPL-2.0-only WITH GCC-exception-2.0",
+    ),
+    ("GPL-3.0", "GPL-3.0-only"),
+    (
+        "GPL-3.0-with-autoconf-exception",
+        "GPL-3.0-only WITH Autoconf-exception-3.0",
+    ),
+    (
+        "GPL-3.0-with-GCC-exception",
+        "GPL-3.0-only WITH GCC-exception-3.1",
+    ),
+    ("LGPL-2.0", "LGPL-2.0-only"),
+    ("LGPL-2.1", "LGPL-2.1-only"),
+    ("LGPL-3.0", "LGPL-3.0-only"),
+    ("Nunit", "zlib-acknowledgement"),
+    ("StandardML-NJ", "SMLNJ"),
+];
+
+/// Rewrite deprecated identifiers in `license` to their current forms.
+/// Returns `None` when there's nothing to replace.
+fn normalize_license(license: &str, expression: &spdx::Expression) -> Option<String> {
+    let mut normalized = String::with_capacity(license.len());
+    let mut end = 0;
+
+    for er in expression.requirements() {
+        let spdx::LicenseItem::Spdx { id, .. } = er.req.license else {
+            continue;
+        };
+        if !id.is_deprecated() {
+            continue;
+        }
+
+        let Some(&(_, current)) = DEPRECATED_LICENSES.iter().find(|(old, _)| *old == id.name)
+        else {
+            continue;
+        };
+
+        // The old identifiers with an exception baked in can't take
+        // another one
+        if current.contains(" WITH ") && er.req.addition.is_some() {
+            continue;
+        }
+
+        let span = er.span.start as usize..er.span.end as usize;
+        normalized.push_str(&license[end..span.start]);
+        normalized.push_str(current);
+        end = span.end;
+    }
+
+    if end == 0 {
+        return None;
+    }
+
+    normalized.push_str(&license[end..]);
+    Some(normalized)
+}
+
+fn parse_license(license: &str) -> Result<spdx::Expression, spdx::error::ParseError> {
+    // Lax mode accepts the `/` separator and imprecise names still common on
+    // crates.io
+    let expression = spdx::Expression::parse_mode(license, spdx::ParseMode::LAX)?;
+
+    match normalize_license(license, &expression) {
+        Some(normalized) => spdx::Expression::parse_mode(&normalized, spdx::ParseMode::LAX),
+        None => Ok(expression),
+    }
+}
+
+/// Why a `license` field isn't a valid SPDX expression as it stands.
+#[derive(Debug)]
+pub enum SpdxError {
+    /// Understood with the leniency applied when reading licenses, such as
+    /// the `/` separator, imprecise names or deprecated identifiers. Holds
+    /// the expression it's read as.
+    NonCanonical(String),
+    /// Not an SPDX expression even with that leniency
+    Invalid(spdx::error::ParseError),
+}
+
+/// Check that a `license` field is a valid SPDX expression as it stands,
+/// telling common mistakes that are still understood from expressions that
+/// can't be read at all.
+pub fn validate_spdx(license: &str) -> Result<(), SpdxError> {
+    if spdx::Expression::parse_mode(license, spdx::ParseMode::STRICT).is_ok() {
+        return Ok(());
+    }
+
+    let expression = parse_license(license).map_err(SpdxError::Invalid)?;
+    Err(SpdxError::NonCanonical(
+        LicenseExpression(expression).canonical(),
+    ))
+}
+
+fn package_licenses(package: &cargo_metadata::Package) -> Licenses<'_> {
+    if let Some(ref license_str) = package.license {
+        return match parse_license(license_str) {
+            Ok(expression) => Licenses::List(Box::new(LicenseExpression(expression))),
+            Err(_) => Licenses::Invalid(license_str),
+        };
+    }
+
+    if let Some(ref license_file) = package.license_file() {
+        return Licenses::File(license_file.to_string(), None);
+    }
+
+    Licenses::Missing
+}
+
+/// License files of a crate. Ordered by name and version like the
+/// dependencies, and the files of a crate by path, so the license texts are
+/// printed in the same order on every run regardless of the order the file
+/// system lists them in.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LicenseTable {
+    pub name: String,
+    pub version: String,
+    pub license_files: BTreeSet<camino::Utf8PathBuf>,
+}
+
+/// Directory of a package with symlinks resolved.
+fn package_dir(package: &cargo_metadata::Package) -> io::Result<camino::Utf8PathBuf> {
+    package
+        .manifest_path
+        .parent()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Package manifest path missing"))?
+        .canonicalize_utf8()
+}
+
+/// Why the declared `license_file` of a package can't be used.
+#[derive(Debug)]
+enum LicenseFileError {
+    /// The file lies outside of the package directory
+    Outside(camino::Utf8PathBuf),
+    /// The file doesn't exist
+    NotFound(camino::Utf8PathBuf),
+}
+
+/// Resolve the declared `license_file` of a package, `None` if there isn't
+/// one. The path is relative to the directory of the manifest and may point
+/// into a subdirectory, such as `docs/LICENSE.custom`.
+///
+/// Crates from registries and git repositories must keep their license file
+/// within the package directory, otherwise [`LicenseFileError::Outside`] is
+/// returned. Local crates may also point outside of it, typically to the
+/// license file at the root of their workspace.
+fn declared_license_file(
+    package: &cargo_metadata::Package,
+) -> Result<Option<camino::Utf8PathBuf>, LicenseFileError> {
+    let (Some(license_file), Ok(dir)) = (package.license_file(), package_dir(package)) else {
+        return Ok(None);
+    };
+
+    let path = dir.join(&license_file);
+    let Ok(file) = path.canonicalize_utf8() else {
+        return Err(LicenseFileError::NotFound(path));
+    };
+
+    if package.source.is_some() && !file.starts_with(&dir) {
+        return Err(LicenseFileError::Outside(file));
+    }
+
+    Ok(Some(file))
+}
+
+/// Files cargo adds when it unpacks a crate into the registry cache or
+/// `cargo vendor` copies it into a vendor directory.
+const CARGO_MARKER_FILES: &[&str] = &[".cargo-ok", ".cargo-checksum.json"];
+
+/// Total size of the files in the directory of a package. Build output and
+/// version control data of local crates aren't part of the package, and
+/// symlinks aren't followed. Cargo's own marker files are left out so a
+/// vendored crate has the same size as one in the registry cache.
+fn package_size(package: &cargo_metadata::Package) -> io::Result<u64> {
+    fn dir_size(dir: &std::path::Path, top: bool) -> io::Result<u64> {
+        let mut size = 0;
+        for entry in dir.read_dir()?.flatten() {
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                if entry.file_name() != "target" && entry.file_name() != ".git" {
+                    size += dir_size(&entry.path(), false)?;
+                }
+            } else if file_type.is_file() {
+                if top
+                    && CARGO_MARKER_FILES
+                        .iter()
+                        .any(|name| entry.file_name() == *name)
+                {
+                    continue;
+                }
+                size += entry.metadata()?.len();
+            }
+        }
+        Ok(size)
+    }
+
+    dir_size(package_dir(package)?.as_std_path(), true)
+}
+
+/// Size in bytes in binary units, such as `12.3 KiB`.
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if size < 1024 {
+        return format!("{size} B");
+    }
+
+    let mut size = size as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// Find the license files of a package: the declared `license_file` and the
+/// files in its directory named like licenses. Warnings about files that are
+/// skipped are added to `warnings` for the caller to print.
+pub fn package_license_files(
+    package: &cargo_metadata::Package,
+    options: &LicenseFileOptions,
+    warnings: &mut Vec<String>,
+) -> io::Result<BTreeSet<camino::Utf8PathBuf>> {
+    let mut result = BTreeSet::new();
+
+    // Canonical paths so the declared file and the same file found by
+    // scanning the directory end up as one entry
+    let path = package_dir(package)?;
+
+    match declared_license_file(package) {
+        Ok(Some(file)) => {
+            result.insert(file);
+        }
+        Ok(None) => {}
+        Err(LicenseFileError::Outside(file)) => warnings.push(format!(
+            "skipping license file of {} {} outside of the package: {file}",
+            package.name, package.version
+        )),
+        Err(LicenseFileError::NotFound(file)) => warnings.push(format!(
+            "license file of {} {} not found: {file}",
+            package.name, package.version
+        )),
+    }
+
+    for entry in path.read_dir()?.flatten() {
+        // Skip directories such as `licenses/`
+        if !entry.path().is_file() {
+            continue;
+        }
+
+        if !options.is_license_file(&entry.file_name().to_string_lossy()) {
+            continue;
+        }
+
+        match camino::Utf8PathBuf::from_path_buf(entry.path()) {
+            Ok(path) => {
+                result.insert(path);
+            }
+            Err(path) => warnings.push(format!(
+                "skipping license file with non-UTF-8 path: {}",
+                path.display()
+            )),
+        }
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,79 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use cargo_metadata::camino::{Utf8Path, Utf8PathBuf};
+use itertools::Itertools;
+
+use crate::{Error, LicenseTable};
+
+/// Name of the file listing the copied license files.
+const MANIFEST: &str = "MANIFEST.txt";
+
+/// The innermost directory all the license files of a crate are in.
+fn common_dir(files: &BTreeSet<Utf8PathBuf>) -> Option<&Utf8Path> {
+    let mut files = files.iter();
+    let mut dir = files.next()?.parent()?;
+    for file in files {
+        while !file.starts_with(dir) {
+            dir = dir.parent()?;
+        }
+    }
+    Some(dir)
+}
+
+/// Copy the license files of each crate to `dir/<name>-<version>/` and write
+/// a manifest listing them. The files keep their paths relative to each
+/// other, so a `LICENSE` and a declared `third_party/LICENSE` don't
+/// overwrite each other. Files that can't be copied are skipped with a
+/// warning and left out of the manifest.
+pub fn write(dir: &Path, licenses_list: &BTreeSet<LicenseTable>) -> crate::Result<()> {
+    let mut manifest = Vec::new();
+
+    for LicenseTable {
+        name,
+        version,
+        license_files,
+    } in licenses_list
+    {
+        if license_files.is_empty() {
+            continue;
+        }
+
+        let crate_dir = format!("{name}-{version}");
+        let target_dir = dir.join(&crate_dir);
+        let Some(source_dir) = common_dir(license_files) else {
+            continue;
+        };
+
+        for file in license_files {
+            let Ok(relative) = file.strip_prefix(source_dir) else {
+                continue;
+            };
+
+            let target = target_dir.join(relative);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|source| Error::CreateDir {
+                    path: parent.to_path_buf(),
+                    source,
+                })?;
+            }
+            // Like an unreadable file when printing license texts, a file
+            // that can't be copied is left out rather than failing the BOM
+            if let Err(err) = fs::copy(file, &target) {
+                eprintln!(
+                    "warning: failed to read license file of {name} {version}: {file}: {err}"
+                );
+                continue;
+            }
+
+            // The same on every platform
+            let relative = relative.components().map(|c| c.as_str()).join("/");
+            writeln!(manifest, "{name} {version}: {crate_dir}/{relative}")?;
+        }
+    }
+
+    let path = dir.join(MANIFEST);
+    fs::write(&path, manifest).map_err(|source| Error::WriteFile { path, source })
+}
@@ -1,13 +1,23 @@
-use std::collections::BTreeSet;
-use std::fmt;
-use std::io::{self, Write};
-use std::path::PathBuf;
+use std::collections::{BTreeSet, HashSet};
+use std::fs::{self, File};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-use cargo_metadata::{camino, DependencyKind};
-use itertools::Itertools;
-use tabled::Tabled;
+use anyhow::{anyhow, Context};
 
-use clap::{Parser, Subcommand};
+use cargo_metadata::CargoOpt;
+
+use clap::{Args, ColorChoice, CommandFactory, Parser, Subcommand, ValueEnum};
+
+use cargo_bom::{
+    attribution, build_bom, compatibility, config::Config, copyleft, count_merged_dependencies,
+    csv, cyclonedx, diff, duplicates, html, json, license_dir, markdown, members, merge_boms, ort,
+    policy, spdx_doc, summary, template, toml, tsv, validate_spdx, write_license_texts,
+    write_table, AuthorEmails, Bom, BomOptions, Column, DedupStrategy, Delimiters, DepTable, Kind,
+    LicenseFileOptions, LicenseTextOptions, Licenses, Platform, SortBy, SpdxError, TableOptions,
+    TableStyle,
+};
 
 #[derive(Debug, Parser)]
 #[command(version, about, long_about = None)]
@@ -18,200 +28,1273 @@ struct Cli {
 
 #[derive(Debug, Subcommand)]
 enum BomCli {
-    Bom {
-        /// Path to Cargo.toml
-        #[arg(long)]
-        manifest_path: Option<PathBuf>,
+    Bom(BomArgs),
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Print a shell completion script for cargo-bom
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Check that a committed BOM is up to date, printing a diff and failing
+    /// if the BOM generated with the other options differs from it. The
+    /// options to generate the BOM with go before `verify`, as in
+    /// `cargo bom --style ascii verify --against BOM.txt`. --license-dir and
+    /// --notices-file still write their files
+    Verify {
+        /// The committed BOM
+        #[arg(long, value_name = "PATH")]
+        against: PathBuf,
     },
 }
 
-fn main() -> anyhow::Result<()> {
-    let cli = Cli::parse();
+#[derive(Debug, Default, Args)]
+struct BomArgs {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Path to Cargo.toml or the directory containing it, can be repeated
+    /// to merge the dependencies of several workspaces into one BOM
+    #[arg(long)]
+    manifest_path: Vec<PathBuf>,
+
+    /// Write the output to a file instead of stdout
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+
+    /// Only include the dependencies of the given workspace package, can be
+    /// repeated. With several --manifest-path it may be in any of them
+    #[arg(long, short, value_name = "NAME")]
+    package: Vec<String>,
+
+    /// Include the dependencies of all workspace members (default)
+    #[arg(long, conflicts_with = "package")]
+    workspace: bool,
+
+    /// Leave out crates whose name matches the given glob pattern, can be
+    /// repeated
+    #[arg(long, value_name = "NAME")]
+    exclude: Vec<glob::Pattern>,
+
+    /// Also treat files whose name matches the given glob pattern as license
+    /// files, regardless of case, can be repeated
+    #[arg(long, value_name = "PATTERN")]
+    license_filename: Vec<glob::Pattern>,
+
+    /// Skip license files whose name matches the given glob pattern,
+    /// regardless of case, can be repeated. A declared `license-file` is
+    /// always kept
+    #[arg(long, value_name = "GLOB")]
+    exclude_license_files: Vec<glob::Pattern>,
+
+    /// List the workspace members themselves too, marked in a `Workspace`
+    /// column
+    #[arg(long)]
+    include_workspace_members: bool,
+
+    /// List crates included by `path` from outside of the workspace, usually
+    /// first-party code, which are left out by default
+    #[arg(long, overrides_with = "exclude_path_dependencies")]
+    include_path_dependencies: bool,
+
+    /// Leave out crates included by `path` from outside of the workspace
+    /// [default]
+    #[arg(long)]
+    exclude_path_dependencies: bool,
+
+    /// Space or comma separated list of features to activate
+    #[arg(long, short = 'F')]
+    features: Vec<String>,
+
+    /// Activate all available features
+    #[arg(long)]
+    all_features: bool,
+
+    /// Do not activate the `default` feature
+    #[arg(long)]
+    no_default_features: bool,
+
+    /// Only include dependencies active for the given target triple
+    /// [default: all targets]
+    #[arg(long, value_name = "TRIPLE")]
+    target: Option<String>,
+
+    /// Only include platform-specific dependencies whose `cfg` holds with
+    /// the given option set on top of those of the target, as with
+    /// `RUSTFLAGS=--cfg`, can be repeated
+    #[arg(long, value_name = "SPEC")]
+    cfg: Vec<cargo_platform::Cfg>,
+
+    /// Don't print cargo's progress messages nor the summary line
+    #[arg(long, short)]
+    quiet: bool,
+
+    /// Require Cargo.lock to be up to date
+    #[arg(long)]
+    locked: bool,
+
+    /// Run without accessing the network
+    #[arg(long)]
+    offline: bool,
+
+    /// Equivalent to both --locked and --offline
+    #[arg(long)]
+    frozen: bool,
+
+    /// Copy the license files of each crate to `DIR/<name>-<version>/`
+    /// instead of printing the license texts
+    #[arg(long, value_name = "DIR")]
+    license_dir: Option<PathBuf>,
+
+    /// Write the license texts to a file of their own instead of printing
+    /// them after the table
+    #[arg(long, value_name = "PATH")]
+    notices_file: Option<PathBuf>,
+
+    /// Cut off license files longer than the given number of bytes, marking
+    /// them with `[truncated]` [default: unlimited]
+    #[arg(long, value_name = "BYTES")]
+    max_license_bytes: Option<u64>,
+
+    /// Include the license files of each crate, base64-encoded, in JSON
+    /// output
+    #[arg(long)]
+    embed_license_text: bool,
+
+    /// Also write the BOM in the JSON format to a file, whatever the format
+    /// of the output, without resolving the dependencies a second time
+    #[arg(long, value_name = "PATH")]
+    json_output: Option<PathBuf>,
+
+    /// Output format [default: table]
+    #[arg(long, value_enum)]
+    format: Option<Format>,
+
+    /// Render the BOM with a Handlebars template instead of an output format
+    #[arg(long, value_name = "FILE", conflicts_with = "format")]
+    template: Option<PathBuf>,
+
+    /// Border style of the table [default: modern, or ascii on terminals
+    /// that don't use UTF-8]
+    #[arg(long, value_enum)]
+    style: Option<TableStyle>,
+
+    /// Highlight crates with missing or unclear licenses in the table
+    /// [default: auto]
+    #[arg(long, value_enum, default_value_t, hide_default_value = true)]
+    color: ColorChoice,
+
+    /// Columns of the table and their order [default: name,version,licenses]
+    #[arg(long, value_enum, value_delimiter = ',')]
+    columns: Vec<Column>,
+
+    /// Add a column with the source repository of each crate
+    #[arg(long)]
+    show_repository: bool,
+
+    /// Add a column with the authors of each crate
+    #[arg(long)]
+    show_authors: bool,
+
+    /// Add a column with the registry, git repository or path each crate
+    /// comes from
+    #[arg(long)]
+    show_source: bool,
+
+    /// Add a column with the size of each crate's source on disk
+    #[arg(long)]
+    show_size: bool,
+
+    /// Add a column with the description of each crate
+    #[arg(long)]
+    show_description: bool,
+
+    /// Add a column with the features enabled for each crate in the
+    /// resolved dependency graph
+    #[arg(long)]
+    show_features: bool,
+
+    /// Wrap the table to fit within this many characters [default: the
+    /// width of the terminal, unlimited when not writing to one]
+    #[arg(long, value_name = "CHARS")]
+    max_width: Option<usize>,
+
+    /// Truncate descriptions in the table to this many characters, 0 for no
+    /// limit
+    #[arg(long, value_name = "CHARS", default_value_t = 60)]
+    description_width: usize,
+
+    /// Only list the crates that appear in more than one version
+    #[arg(long, conflicts_with_all = ["group_by_license", "attribution"])]
+    show_duplicates: bool,
+
+    /// Print a third-party notices document with the license texts of each
+    /// crate instead of the table
+    #[arg(long, conflicts_with = "group_by_license")]
+    attribution: bool,
+
+    /// List the dependencies that can only be used under a copyleft license
+    /// after the table
+    #[arg(
+        long,
+        conflicts_with_all = ["attribution", "show_duplicates", "group_by_license", "by_member"]
+    )]
+    highlight_copyleft: bool,
+
+    /// Only print the table of dependencies, without the license texts
+    #[arg(long)]
+    no_license_text: bool,
+
+    /// Print each distinct license text only once, listing the crates using
+    /// it
+    #[arg(long)]
+    dedup_licenses: bool,
+
+    /// Markers around the license texts after the table
+    #[arg(long, value_enum, default_value_t)]
+    delimiters: Delimiters,
+
+    /// Order of the dependencies in the output
+    #[arg(long, value_enum, default_value_t)]
+    sort_by: SortBy,
+
+    /// Reverse the order given by --sort-by
+    #[arg(long)]
+    reverse: bool,
+
+    /// Whether each version of a crate gets a row of its own or the
+    /// versions share one. The cyclonedx, spdx, ort and toml formats,
+    /// --by-member and --show-duplicates always list each version
+    #[arg(long, value_enum, default_value_t)]
+    dedup_strategy: DedupStrategy,
+
+    /// Whether email addresses of the authors are kept or left out, for
+    /// publishing the BOM
+    #[arg(long, value_enum, default_value_t)]
+    author_emails: AuthorEmails,
+
+    /// Only print the number of dependencies, without looking at their
+    /// licenses
+    #[arg(long, conflicts_with = "output")]
+    count: bool,
+
+    /// Compare the dependencies with a BOM written earlier with --format json
+    /// and print what changed instead of the BOM, failing if anything did
+    #[arg(long, value_name = "SNAPSHOT")]
+    diff: Option<PathBuf>,
+
+    /// Print each distinct license with the crates using it instead of the
+    /// table of dependencies and license texts
+    #[arg(long)]
+    group_by_license: bool,
+
+    /// List the direct dependencies of each workspace member under its name
+    /// instead of one table of all dependencies
+    #[arg(long, conflicts_with_all = ["group_by_license", "attribution", "show_duplicates"])]
+    by_member: bool,
+
+    /// Write a complete HTML document with styles and sortable columns
+    /// instead of a fragment to embed
+    #[arg(long)]
+    standalone: bool,
+
+    /// Omit the header row from CSV and TSV output
+    #[arg(long)]
+    no_headers: bool,
+
+    /// Kinds of dependencies to include [default: normal]
+    #[arg(long, value_enum, value_delimiter = ',')]
+    kind: Vec<Kind>,
+
+    /// Maximum depth of the dependency graph to include, 1 lists only
+    /// direct dependencies [default: unlimited]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    depth: Option<u32>,
+
+    /// Allow only the given license, can be repeated
+    #[arg(long, value_name = "SPDX")]
+    allow: Vec<spdx::Licensee>,
+
+    /// Deny the given license, can be repeated
+    #[arg(long, value_name = "SPDX")]
+    deny: Vec<spdx::Licensee>,
+
+    /// Leave out crates that can be used under the given licenses only, can
+    /// be repeated
+    #[arg(long, value_name = "SPDX")]
+    hide_license: Vec<spdx::Licensee>,
+
+    /// Identify the license of crates that only have a license file from the
+    /// text of the file
+    #[arg(long)]
+    detect_licenses: bool,
+
+    /// Report problems as GitHub Actions error annotations [default: when
+    /// running in GitHub Actions]
+    #[arg(long)]
+    github_annotations: bool,
+
+    /// Mark the dependencies whose version has been yanked from crates.io,
+    /// according to cargo's cached copy of the index
+    #[arg(long)]
+    check_yanked: bool,
+
+    /// Fail if a dependency has been yanked from crates.io, implies
+    /// --check-yanked
+    #[arg(long)]
+    fail_on_yanked: bool,
+
+    /// Fail if a dependency has neither a license nor a license file
+    #[arg(long)]
+    fail_on_missing_license: bool,
+
+    /// Fail if a dependency declares a license but ships no license file,
+    /// leaving nothing to reproduce in the attribution
+    #[arg(long)]
+    require_license_text: bool,
+
+    /// Fail if the license field of a dependency isn't an SPDX expression,
+    /// and warn about ones only understood leniently such as `MIT/Apache-2.0`
+    #[arg(long)]
+    validate_spdx: bool,
+
+    /// Fail if a dependency can't be used under any license approved by the
+    /// Open Source Initiative
+    #[arg(long)]
+    osi_approved_only: bool,
+
+    /// Fail on any problem found: implies --fail-on-missing-license,
+    /// --require-license-text, --validate-spdx and --fail-on-yanked, and
+    /// makes incompatible licenses an error
+    #[arg(long)]
+    strict: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// Table of dependencies followed by their license texts
+    #[default]
+    Table,
+    /// Machine-readable JSON document
+    Json,
+    /// One JSON object per dependency and line, for streaming
+    #[value(name = "jsonl", alias = "json-lines")]
+    JsonLines,
+    /// CycloneDX JSON software bill of materials
+    #[value(name = "cyclonedx")]
+    CycloneDx,
+    /// SPDX 2.3 tag-value document
+    Spdx,
+    /// GitHub-flavored Markdown table followed by fenced license texts
+    Markdown,
+    /// HTML table followed by collapsible license texts
+    Html,
+    /// Comma-separated values
+    Csv,
+    /// Tab-separated values without quoting
+    Tsv,
+    /// TOML document for committing into a repository
+    Toml,
+    /// OSS Review Toolkit analyzer result
+    Ort,
+}
+
+/// Exit status when the dependencies violate the license policy, including
+/// `--osi-approved-only`.
+const EXIT_POLICY_VIOLATION: u8 = 2;
+/// Exit status when dependencies have no license or no license text.
+const EXIT_MISSING_LICENSE: u8 = 3;
+/// Exit status when license expressions are invalid SPDX or, with
+/// `--strict`, combine incompatible licenses.
+const EXIT_INVALID_LICENSE: u8 = 4;
+/// Exit status when dependencies have been yanked.
+const EXIT_YANKED: u8 = 5;
+/// Exit status when dependencies changed since the `--diff` snapshot or the
+/// BOM given to `verify` is out of date.
+const EXIT_OUT_OF_DATE: u8 = 6;
+
+/// Checks that failed, exiting with a status of their own so CI can tell
+/// them from cargo-bom itself failing, which exits with 1.
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+struct CheckFailure {
+    message: String,
+    status: u8,
+}
+
+fn main() -> ExitCode {
+    // clap exits with 2 on usage errors, which would read as a policy
+    // violation
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) if err.use_stderr() => {
+            let _ = err.print();
+            return ExitCode::FAILURE;
+        }
+        Err(err) => err.exit(),
+    };
+
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        // A failed check is a finding rather than an error of the tool, so
+        // it's printed without the chain of causes or a backtrace
+        Err(err) => match err.downcast_ref::<CheckFailure>() {
+            Some(failure) => {
+                eprintln!("Error: {failure}");
+                ExitCode::from(failure.status)
+            }
+            None => {
+                eprintln!("Error: {err:?}");
+                ExitCode::FAILURE
+            }
+        },
+    }
+}
+
+fn run(cli: Cli) -> anyhow::Result<()> {
+    let mut args = match cli.bom {
+        Some(BomCli::Bom(args)) => args,
+        None => BomArgs::default(),
+    };
+    if args.strict {
+        args.fail_on_missing_license = true;
+        args.require_license_text = true;
+        args.validate_spdx = true;
+        args.fail_on_yanked = true;
+    }
+
+    if let Some(Command::Completions { shell }) = args.command {
+        clap_complete::generate(shell, &mut Cli::command(), "cargo-bom", &mut Stdout::new());
+        return Ok(());
+    }
+    let verify = match args.command {
+        Some(Command::Verify { ref against }) => Some(against.clone()),
+        _ => None,
+    };
+
+    // The BOM is only compared when verifying, so these would write nothing
+    if verify.is_some() {
+        for (set, option) in [
+            (args.output.is_some(), "--output"),
+            (args.json_output.is_some(), "--json-output"),
+        ] {
+            if set {
+                anyhow::bail!("{option} can't be used with verify");
+            }
+        }
+    }
+
     let mut cmd = cargo_metadata::MetadataCommand::new();
 
-    if let Some(bom) = cli.bom {
-        match bom {
-            BomCli::Bom { manifest_path } => {
-                if let Some(path) = manifest_path {
-                    cmd.manifest_path(path);
-                }
+    let manifests = if args.manifest_path.is_empty() {
+        vec![find_manifest()?]
+    } else {
+        args.manifest_path
+            .iter()
+            .map(|path| manifest_path(path))
+            .collect::<anyhow::Result<_>>()?
+    };
+
+    if !args.features.is_empty() {
+        // Like cargo, accept both comma and space separated lists
+        let features = args
+            .features
+            .iter()
+            .flat_map(|features| features.split([',', ' ']))
+            .filter(|feature| !feature.is_empty())
+            .map(ToString::to_string)
+            .collect();
+        cmd.features(CargoOpt::SomeFeatures(features));
+    }
+    if args.all_features {
+        cmd.features(CargoOpt::AllFeatures);
+    }
+    if args.no_default_features {
+        cmd.features(CargoOpt::NoDefaultFeatures);
+    }
+
+    let mut other_options = Vec::new();
+
+    // Let cargo prune the dependencies that aren't active on the target
+    if let Some(ref target) = args.target {
+        other_options.extend(["--filter-platform".to_string(), target.clone()]);
+    }
+
+    // Fail rather than update Cargo.lock or download anything, so the BOM
+    // matches the lock file exactly
+    if args.locked {
+        other_options.push("--locked".to_string());
+    }
+    if args.offline {
+        other_options.push("--offline".to_string());
+    }
+    if args.frozen {
+        other_options.push("--frozen".to_string());
+    }
+
+    // Keep "Updating crates.io index" and the like off stderr
+    if args.quiet {
+        other_options.push("--quiet".to_string());
+    }
+
+    cmd.other_options(other_options);
+
+    // The error from cargo alone tends to be a wall of resolver output
+    let mut metadatas = manifests
+        .iter()
+        .map(|manifest| {
+            cmd.clone().manifest_path(manifest).exec().with_context(|| {
+                format!(
+                    "failed to resolve the dependency graph of {} with `cargo metadata`; if \
+                     Cargo.lock is out of date run `cargo update`, or use --offline if the \
+                     network isn't reachable",
+                    manifest.display()
+                )
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    // Settings come from the first workspace when merging several
+    let config = Config::from_metadata(&metadatas[0])?;
+
+    // Each package only has to be in one of the merged workspaces, and the
+    // workspaces without any of them are left out
+    if !args.package.is_empty() {
+        let is_member = |metadata: &cargo_metadata::Metadata, name: &String| {
+            metadata
+                .workspace_members
+                .iter()
+                .any(|id| metadata[id].name == *name)
+        };
+        for name in &args.package {
+            if !metadatas.iter().any(|metadata| is_member(metadata, name)) {
+                let searched: Vec<_> = manifests
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect();
+                anyhow::bail!(
+                    "package `{name}` is not a member of the workspace of {}",
+                    searched.join(", ")
+                );
+            }
+        }
+        metadatas.retain(|metadata| args.package.iter().any(|name| is_member(metadata, name)));
+    }
+    // There's no single root package to describe a merged BOM
+    let root = match metadatas.as_slice() {
+        [metadata] => metadata.root_package(),
+        _ => None,
+    };
+
+    let template = match args.template {
+        Some(ref path) => Some(
+            fs::read_to_string(path)
+                .with_context(|| format!("failed to read template {}", path.display()))?,
+        ),
+        None => None,
+    };
+
+    let format = match (args.format, &config.format) {
+        (Some(format), _) => format,
+        (None, Some(format)) => Format::from_str(format, true)
+            .map_err(|_| anyhow!("invalid format {format:?} in [workspace.metadata.cargo-bom]"))?,
+        (None, None) => Format::default(),
+    };
+
+    // These replace the table, so they'd be silently ignored otherwise
+    if format != Format::Table {
+        for (set, option) in [
+            (args.attribution, "--attribution"),
+            (args.show_duplicates, "--show-duplicates"),
+            (args.group_by_license, "--group-by-license"),
+            (args.by_member, "--by-member"),
+            (args.highlight_copyleft, "--highlight-copyleft"),
+        ] {
+            if set {
+                let name = format.to_possible_value().expect("no skipped formats");
+                anyhow::bail!("{option} can't be used with --format {}", name.get_name());
             }
         }
     }
 
-    let metadata = cmd.exec()?;
+    let kinds = Kind::expand(&args.kind);
+
+    let mut columns = if args.columns.is_empty() {
+        let mut columns = vec![Column::Name, Column::Version, Column::Licenses];
+        if kinds.len() > 1 {
+            columns.push(Column::Kind);
+        }
+        if args.include_workspace_members {
+            columns.push(Column::Workspace);
+        }
+        columns
+    } else {
+        args.columns.clone()
+    };
+    for (show, column) in [
+        (args.show_repository, Column::Repository),
+        (args.show_authors, Column::Authors),
+        (args.show_source, Column::Source),
+        (args.show_description, Column::Description),
+        (args.show_features, Column::Features),
+        (args.show_size || args.sort_by == SortBy::Size, Column::Size),
+    ] {
+        if show && !columns.contains(&column) {
+            columns.push(column);
+        }
+    }
+
+    // Evaluating `cfg` expressions needs the options rustc sets itself
+    let platform = if args.cfg.is_empty() {
+        None
+    } else {
+        Some(target_platform(args.target.as_deref(), args.cfg)?)
+    };
+
+    // A row of collapsed versions only has the package of the newest one, so
+    // formats that identify each package, members depending on a particular
+    // version and the list of duplicates keep the versions apart
+    let dedup = match format {
+        Format::CycloneDx | Format::Spdx | Format::Ort | Format::Toml => DedupStrategy::NameVersion,
+        _ if args.by_member || args.show_duplicates => DedupStrategy::NameVersion,
+        _ => args.dedup_strategy,
+    };
+
+    let options = BomOptions {
+        packages: args.package,
+        kinds: args.kind,
+        depth: args.depth,
+        platform,
+        exclude: args.exclude,
+        hide_licenses: args.hide_license,
+        detect_licenses: args.detect_licenses,
+        include_workspace_members: args.include_workspace_members,
+        include_path_dependencies: args.include_path_dependencies,
+        sizes: columns.contains(&Column::Size),
+        check_yanked: args.check_yanked || args.fail_on_yanked,
+        sort_by: args.sort_by,
+        dedup,
+        author_emails: args.author_emails,
+        license_files: LicenseFileOptions {
+            extra_names: args.license_filename,
+            exclude: args.exclude_license_files,
+        },
+        reverse: args.reverse,
+        // indicatif draws nothing when stderr isn't a terminal
+        progress: !args.quiet,
+    };
+
+    if args.count {
+        let counts = count_merged_dependencies(&metadatas, &options)?;
+        println!(
+            "{} dependencies, {} distinct crates",
+            counts.total, counts.distinct
+        );
+        return Ok(());
+    }
+
+    let Bom {
+        dependencies: depencies_list,
+        licenses: licenses_list,
+    } = merge_boms(
+        metadatas
+            .iter()
+            .map(|metadata| build_bom(metadata, &options))
+            .collect::<cargo_bom::Result<_>>()?,
+        &options,
+    );
+
+    // Licenses given on the command line replace those from Cargo.toml
+    let policy = policy::Policy {
+        allow: if args.allow.is_empty() {
+            config.allow()?
+        } else {
+            args.allow
+        },
+        deny: if args.deny.is_empty() {
+            config.deny()?
+        } else {
+            args.deny
+        },
+    };
+    let violations = policy.violations(&depencies_list);
 
-    let mut depencies_list = BTreeSet::new();
-    let mut licenses_list = BTreeSet::new();
+    // Crates without a `license` field nor a license file
+    let missing_licenses: Vec<&DepTable> = if args.fail_on_missing_license {
+        depencies_list
+            .iter()
+            .filter(|dep| dep.licenses == Licenses::Missing)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    // Crates with a `license` field whose license text can't be reproduced
+    let missing_texts: Vec<&DepTable> = if args.require_license_text {
+        let without_files: HashSet<(&str, &str)> = licenses_list
+            .iter()
+            .filter(|table| table.license_files.is_empty())
+            .map(|table| (table.name.as_str(), table.version.as_str()))
+            .collect();
+        depencies_list
+            .iter()
+            .filter(|dep| matches!(dep.licenses, Licenses::List(_) | Licenses::Invalid(_)))
+            .filter(|dep| without_files.contains(&(dep.name.as_str(), dep.version.as_str())))
+            .collect()
+    } else {
+        Vec::new()
+    };
 
-    let members = metadata.workspace_packages();
+    let yanked: Vec<&DepTable> = if args.fail_on_yanked {
+        depencies_list
+            .iter()
+            .filter(|dep| dep.yanked == Some(true))
+            .collect()
+    } else {
+        Vec::new()
+    };
 
-    for member in &members {
-        for dependency in &member.dependencies {
-            // We only care about normal dependencies
-            if dependency.kind != DependencyKind::Normal {
+    let not_osi_approved: Vec<&DepTable> = if args.osi_approved_only {
+        depencies_list
+            .iter()
+            .filter(|dep| !policy::osi_approved(&dep.licenses))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    // Malformed license fields, even those cargo-bom manages to make sense of
+    let mut invalid_spdx: Vec<(&DepTable, &str, spdx::error::ParseError)> = Vec::new();
+    // Fixed in their `license` field these would be reported upstream, but
+    // they don't leave the license in doubt
+    let mut non_canonical_spdx: Vec<(&DepTable, &str, String)> = Vec::new();
+    if args.validate_spdx {
+        for dep in &depencies_list {
+            let Some(license) = dep.package.license.as_deref() else {
                 continue;
+            };
+            match validate_spdx(license) {
+                Ok(()) => {}
+                Err(SpdxError::Invalid(err)) => invalid_spdx.push((dep, license, err)),
+                Err(SpdxError::NonCanonical(canonical)) => {
+                    non_canonical_spdx.push((dep, license, canonical))
+                }
             }
+        }
+    }
+
+    // A BOM being verified is compared to a file, so it's rendered like one
+    let to_terminal = args.output.is_none() && verify.is_none() && io::stdout().is_terminal();
+
+    // Box-drawing characters turn into mojibake on terminals that don't
+    // speak UTF-8
+    let style = args.style.unwrap_or_else(|| {
+        if to_terminal && !utf8_console() {
+            TableStyle::Ascii
+        } else {
+            TableStyle::Modern
+        }
+    });
+
+    let max_width = args.max_width.or_else(|| {
+        if !to_terminal {
+            return None;
+        }
+        terminal_size::terminal_size().map(|(terminal_size::Width(width), _)| width.into())
+    });
+
+    // Only the table is colored, machine-readable formats never are
+    let color = match args.color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => to_terminal && std::env::var_os("NO_COLOR").is_none(),
+    };
+
+    let mut rendered = Vec::new();
+    let mut out: Box<dyn Write> = match args.output {
+        _ if verify.is_some() => Box::new(&mut rendered),
+        Some(ref path) => Box::new(io::BufWriter::new(create_output(path)?)),
+        None => Box::new(Stdout::new()),
+    };
+
+    let text_options = LicenseTextOptions {
+        dedup: args.dedup_licenses,
+        delimiters: args.delimiters,
+        max_bytes: args.max_license_bytes,
+    };
+
+    let embed = args.embed_license_text.then_some(json::Embed {
+        licenses: &licenses_list,
+        max_bytes: args.max_license_bytes,
+    });
+
+    // License texts go to the directory or file instead of the output
+    if let Some(ref dir) = args.license_dir {
+        license_dir::write(dir, &licenses_list)?;
+    }
+    if let Some(ref path) = args.notices_file {
+        let mut notices = io::BufWriter::new(create_output(path)?);
+        write_license_texts(&mut notices, &licenses_list, text_options)?;
+        notices.flush()?;
+    }
+    if let Some(ref path) = args.json_output {
+        let mut json_out = io::BufWriter::new(create_output(path)?);
+        json::write(&mut json_out, &depencies_list, embed)?;
+        json_out.flush()?;
+    }
 
-            if let Some(dep) = metadata.packages.iter().find(|p| p.name == dependency.name) {
-                // Skip crates in repository
-                if members.iter().any(|m| m.name == dep.name) {
-                    continue;
+    let no_licenses = BTreeSet::new();
+    let printed_licenses =
+        if args.no_license_text || args.license_dir.is_some() || args.notices_file.is_some() {
+            &no_licenses
+        } else {
+            &licenses_list
+        };
+
+    // The comparison replaces the BOM itself
+    let changes = match args.diff {
+        Some(ref snapshot) => Some(diff::diff(diff::read_snapshot(snapshot)?, &depencies_list)),
+        None => None,
+    };
+
+    if let Some(ref changes) = changes {
+        diff::write(&mut out, changes)?;
+    } else if let Some(ref template) = template {
+        template::write(
+            &mut out,
+            template,
+            &depencies_list,
+            printed_licenses,
+            args.max_license_bytes,
+        )?;
+    } else {
+        match format {
+            Format::Table if args.attribution => attribution::write(
+                &mut out,
+                &depencies_list,
+                &licenses_list,
+                args.max_license_bytes,
+            )?,
+            Format::Table if args.show_duplicates => {
+                duplicates::write(&mut out, &depencies_list, style)?
+            }
+            Format::Table if args.group_by_license => {
+                summary::write(&mut out, &depencies_list, style)?
+            }
+            Format::Table if args.by_member => {
+                for (i, metadata) in metadatas.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(out)?;
+                    }
+                    members::write(&mut out, metadata, &options, &depencies_list, style)?;
+                }
+            }
+            Format::Table => {
+                let options = TableOptions {
+                    style,
+                    columns: &columns,
+                    color,
+                    description_width: args.description_width,
+                    max_width,
+                };
+                write_table(&mut out, &depencies_list, &options)?;
+                if args.highlight_copyleft {
+                    copyleft::write(&mut out, &depencies_list, style)?;
                 }
+                write_license_texts(&mut out, printed_licenses, text_options)?;
+            }
+            Format::Json => json::write(&mut out, &depencies_list, embed)?,
+            Format::JsonLines => json::write_lines(&mut out, &depencies_list, embed)?,
+            Format::CycloneDx => cyclonedx::write(&mut out, root, &depencies_list)?,
+            Format::Spdx => spdx_doc::write(&mut out, root, &depencies_list)?,
+            Format::Markdown => markdown::write(
+                &mut out,
+                &depencies_list,
+                printed_licenses,
+                &columns,
+                text_options,
+            )?,
+            Format::Html => html::write(
+                &mut out,
+                &depencies_list,
+                printed_licenses,
+                &columns,
+                text_options,
+                args.standalone,
+            )?,
+            Format::Csv => csv::write(&mut out, &depencies_list, !args.no_headers, &columns)?,
+            Format::Tsv => tsv::write(&mut out, &depencies_list, !args.no_headers, &columns)?,
+            Format::Toml => toml::write(&mut out, &depencies_list)?,
+            Format::Ort => ort::write(&mut out, &metadatas, &options, &depencies_list)?,
+        }
+    }
+
+    out.flush()?;
+    drop(out);
+
+    // The committed BOM if it differs from the generated one
+    let outdated = match verify {
+        Some(ref path) if verify_bom(path, &rendered)? => Some(path),
+        _ => None,
+    };
 
-                let name = dep.name.clone();
-                let version = dep.version.to_string();
-                let licenses = package_licenses(dep).to_string();
-                let license_files = package_license_files(dep)?;
-
-                depencies_list.insert(DepTable {
-                    name: name.clone(),
-                    version: version.clone(),
-                    licenses,
-                });
-
-                licenses_list.insert(LicenseTable {
-                    name,
-                    version,
-                    license_files,
-                });
+    // A quick signal for scripts and CI logs without parsing the output
+    if !args.quiet {
+        let missing = depencies_list
+            .iter()
+            .filter(|dep| dep.licenses == Licenses::Missing)
+            .count();
+        eprintln!(
+            "cargo-bom: {} deps, {} distinct licenses, {missing} missing, {} policy violations",
+            depencies_list.len(),
+            summary::distinct_licenses(&depencies_list),
+            violations.len()
+        );
+    }
+
+    // Workflow commands make the problems show up inline in pull requests
+    let annotate =
+        args.github_annotations || std::env::var("GITHUB_ACTIONS").is_ok_and(|v| v == "true");
+    let report = |title: &str, message: String| {
+        if annotate {
+            eprintln!("::error title={title}::{}", escape_workflow_data(&message));
+        } else {
+            eprintln!("{}: {message}", title.to_lowercase());
+        }
+    };
+
+    // Only a heuristic, so these don't fail the command unless asked to
+    let incompatible = compatibility::incompatible(&depencies_list);
+    for (dep, conflicts) in &incompatible {
+        for (a, b) in conflicts {
+            let message = format!(
+                "{} {} combines {a} AND {b}, which are known to be incompatible",
+                dep.name, dep.version
+            );
+            if args.strict {
+                report("Incompatible licenses", message);
+            } else if annotate {
+                eprintln!(
+                    "::warning title=Incompatible licenses::{}",
+                    escape_workflow_data(&message)
+                );
+            } else {
+                eprintln!("warning: {message}");
             }
         }
     }
 
-    fn make_table(list: BTreeSet<DepTable>) -> String {
-        use tabled::settings::{Settings, Style};
-        use tabled::Table;
-        let config = Settings::empty().with(Style::modern());
-        Table::new(list).with(config).to_string()
+    for (dep, license, canonical) in &non_canonical_spdx {
+        let message = format!(
+            "{} {}: {license:?} isn't valid SPDX as written, it means {canonical:?}",
+            dep.name, dep.version
+        );
+        if annotate {
+            eprintln!(
+                "::warning title=Non-canonical license expression::{}",
+                escape_workflow_data(&message)
+            );
+        } else {
+            eprintln!("warning: {message}");
+        }
     }
 
-    let table = make_table(depencies_list);
+    let mut errors = Vec::new();
 
-    let mut out = io::stdout().lock();
+    if !violations.is_empty() {
+        for dep in &violations {
+            report(
+                "License policy violation",
+                format!("{} {} ({})", dep.name, dep.version, dep.licenses),
+            );
+        }
+        errors.push(format!(
+            "{} dependencies violate the license policy",
+            violations.len()
+        ));
+    }
 
-    out.write_all(table.as_bytes())?;
-    out.write_all(b"\n")?;
-    out.flush()?;
+    if !missing_licenses.is_empty() {
+        for dep in &missing_licenses {
+            report("Missing license", format!("{} {}", dep.name, dep.version));
+        }
+        errors.push(format!(
+            "{} dependencies have no license",
+            missing_licenses.len()
+        ));
+    }
 
-    for LicenseTable {
-        name,
-        version,
-        license_files,
-    } in licenses_list
-    {
-        if license_files.is_empty() {
-            continue;
-        }
-
-        writeln!(out, "\n-----BEGIN {name} {version} LICENSES-----")?;
-
-        let mut licenses_to_print = license_files.len();
-        for file in license_files {
-            let buf = std::fs::read(file)?;
-            out.write_all(&buf)?;
-            if licenses_to_print > 1 {
-                out.write_all(b"\n-----NEXT LICENSE-----\n")?;
-                licenses_to_print -= 1;
-            }
+    if !missing_texts.is_empty() {
+        for dep in &missing_texts {
+            report(
+                "Missing license text",
+                format!("{} {} ({})", dep.name, dep.version, dep.licenses),
+            );
         }
+        errors.push(format!(
+            "{} dependencies ship no license text",
+            missing_texts.len()
+        ));
+    }
+
+    if args.strict && !incompatible.is_empty() {
+        errors.push(format!(
+            "{} dependencies combine incompatible licenses",
+            incompatible.len()
+        ));
+    }
+
+    if !yanked.is_empty() {
+        for dep in &yanked {
+            report("Yanked crate", format!("{} {}", dep.name, dep.version));
+        }
+        errors.push(format!("{} dependencies have been yanked", yanked.len()));
+    }
+
+    if !not_osi_approved.is_empty() {
+        for dep in &not_osi_approved {
+            report(
+                "Unapproved license",
+                format!("{} {} ({})", dep.name, dep.version, dep.licenses),
+            );
+        }
+        errors.push(format!(
+            "{} dependencies have no OSI-approved license",
+            not_osi_approved.len()
+        ));
+    }
+
+    if !invalid_spdx.is_empty() {
+        for (dep, license, err) in &invalid_spdx {
+            report(
+                "Invalid license expression",
+                format!("{} {}: {license:?}: {}", dep.name, dep.version, err.reason),
+            );
+        }
+        errors.push(format!(
+            "{} dependencies have an invalid SPDX license expression",
+            invalid_spdx.len()
+        ));
+    }
+
+    let changed = changes.as_ref().is_some_and(|changes| !changes.is_empty());
+    if let Some(changes) = changes.filter(|changes| !changes.is_empty()) {
+        errors.push(format!(
+            "{} dependencies changed since the snapshot",
+            changes.len()
+        ));
+    }
+
+    if let Some(path) = outdated {
+        errors.push(format!(
+            "{} is out of date, regenerate it with the same options",
+            path.display()
+        ));
+    }
 
-        writeln!(out, "\n-----END {name} {version} LICENSES-----")?;
-        out.flush()?;
+    if !errors.is_empty() {
+        // The policy comes first when several checks fail
+        let status = if !violations.is_empty() || !not_osi_approved.is_empty() {
+            EXIT_POLICY_VIOLATION
+        } else if !missing_licenses.is_empty() || !missing_texts.is_empty() {
+            EXIT_MISSING_LICENSE
+        } else if !invalid_spdx.is_empty() || (args.strict && !incompatible.is_empty()) {
+            EXIT_INVALID_LICENSE
+        } else if !yanked.is_empty() {
+            EXIT_YANKED
+        } else if changed || outdated.is_some() {
+            EXIT_OUT_OF_DATE
+        } else {
+            1
+        };
+        return Err(CheckFailure {
+            message: errors.join(", "),
+            status,
+        }
+        .into());
     }
 
     Ok(())
 }
 
-static LICENCE_FILE_NAMES: &[&str] = &["LICENSE", "UNLICENSE", "COPYRIGHT"];
+/// Compare a committed BOM with the one just generated, printing a unified
+/// diff of the differences. Returns whether they differ.
+fn verify_bom(path: &Path, rendered: &[u8]) -> anyhow::Result<bool> {
+    let committed = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    if committed == rendered {
+        return Ok(false);
+    }
 
-#[derive(Debug, Tabled, PartialEq, Eq, PartialOrd, Ord)]
-struct DepTable {
-    #[tabled(rename = "Name")]
-    name: String,
-    #[tabled(rename = "Version")]
-    version: String,
-    #[tabled(rename = "Licenses")]
-    licenses: String,
+    let committed = String::from_utf8_lossy(&committed);
+    let rendered = String::from_utf8_lossy(rendered);
+    let diff = similar::TextDiff::from_lines(committed.as_ref(), rendered.as_ref());
+    write!(
+        Stdout::new(),
+        "{}",
+        diff.unified_diff()
+            .header(&path.display().to_string(), "generated")
+    )?;
+    Ok(true)
 }
 
-#[derive(Debug)]
-enum Licenses<'a> {
-    // Use BTreeSet to get alphabetical order automatically.
-    List(BTreeSet<&'a str>),
-    File(String),
-    Missing,
+/// Locked stdout that stops writing once the reader has gone away, as with
+/// `cargo bom | head`, so the checks still run and decide the exit status.
+struct Stdout {
+    inner: io::StdoutLock<'static>,
+    closed: bool,
 }
 
-impl<'a> fmt::Display for Licenses<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        match *self {
-            Licenses::File(_) => write!(f, "Specified in license file"),
-            Licenses::Missing => write!(f, "Missing"),
-            Licenses::List(ref lic_names) => {
-                let lics = lic_names.iter().map(ToString::to_string).join(", ");
-                write!(f, "{}", lics)
+impl Stdout {
+    fn new() -> Stdout {
+        Stdout {
+            inner: io::stdout().lock(),
+            closed: false,
+        }
+    }
+
+    fn ignore_broken_pipe<T>(&mut self, result: io::Result<T>, closed: T) -> io::Result<T> {
+        match result {
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {
+                self.closed = true;
+                Ok(closed)
             }
+            result => result,
         }
     }
 }
 
-fn package_licenses(package: &cargo_metadata::Package) -> Licenses<'_> {
-    if let Some(ref license_str) = package.license {
-        let licenses: BTreeSet<&str> = license_str
-            .split("OR")
-            .flat_map(|s| s.split("AND"))
-            .flat_map(|s| s.split('/'))
-            .map(str::trim)
-            .collect();
-        return Licenses::List(licenses);
+impl Write for Stdout {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.closed {
+            return Ok(buf.len());
+        }
+        let result = self.inner.write(buf);
+        self.ignore_broken_pipe(result, buf.len())
     }
 
-    if let Some(ref license_file) = package.license_file() {
-        return Licenses::File(license_file.to_string());
+    fn flush(&mut self) -> io::Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+        let result = self.inner.flush();
+        self.ignore_broken_pipe(result, ())
     }
+}
 
-    Licenses::Missing
+/// Escape a message of a GitHub Actions workflow command.
+fn escape_workflow_data(message: &str) -> String {
+    message
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-struct LicenseTable {
-    name: String,
-    version: String,
-    license_files: BTreeSet<camino::Utf8PathBuf>,
+#[cfg(windows)]
+fn utf8_console() -> bool {
+    const CP_UTF8: u32 = 65001;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetConsoleOutputCP() -> u32;
+    }
+
+    // SAFETY: GetConsoleOutputCP takes no arguments and has no preconditions.
+    unsafe { GetConsoleOutputCP() == CP_UTF8 }
 }
 
-pub fn package_license_files(
-    package: &cargo_metadata::Package,
-) -> io::Result<BTreeSet<camino::Utf8PathBuf>> {
-    let mut result = BTreeSet::new();
+#[cfg(not(windows))]
+fn utf8_console() -> bool {
+    // The first non-empty locale variable wins, like in setlocale(3). Assume
+    // UTF-8 when the locale isn't set at all.
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+        .is_none_or(|locale| {
+            let locale = locale.to_ascii_uppercase();
+            locale.contains("UTF-8") || locale.contains("UTF8")
+        })
+}
 
-    let path = package
-        .manifest_path
-        .parent()
-        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Package manifest path missing"))?;
+/// Accept the directory of a package in place of its `Cargo.toml`.
+fn manifest_path(path: &Path) -> anyhow::Result<PathBuf> {
+    let manifest = if path.is_dir() {
+        path.join("Cargo.toml")
+    } else {
+        path.to_path_buf()
+    };
 
-    if let Some(ref license_file) = package.license_file() {
-        let file = path.join(license_file);
-        if file.exists() {
-            result.insert(file);
-        }
+    if !manifest.is_file() {
+        anyhow::bail!("manifest path `{}` does not exist", manifest.display());
     }
 
-    for entry in path.read_dir()?.flatten() {
-        if let Ok(name) = entry.file_name().into_string() {
-            for license_name in LICENCE_FILE_NAMES {
-                if name.starts_with(license_name) {
-                    match camino::Utf8PathBuf::from_path_buf(entry.path()) {
-                        Ok(path) => {
-                            result.insert(path);
-                        }
-                        Err(err) => panic!("Invalid path: {err:?}"),
-                    }
-                }
-            }
+    Ok(manifest)
+}
+
+/// Ask rustc for the target triple, the host's by default, and the `cfg`
+/// options active on it, adding the given ones.
+fn target_platform(
+    target: Option<&str>,
+    extra_cfg: Vec<cargo_platform::Cfg>,
+) -> anyhow::Result<Platform> {
+    let rustc = std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let run = |args: &[&str]| -> anyhow::Result<String> {
+        let output = std::process::Command::new(&rustc)
+            .args(args)
+            .output()
+            .context("failed to run rustc to find the cfg options of the target")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "rustc failed to print the cfg options of the target: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
         }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+
+    let target = match target {
+        Some(target) => target.to_string(),
+        None => run(&["-vV"])?
+            .lines()
+            .find_map(|line| line.strip_prefix("host: "))
+            .context("rustc -vV didn't print the host triple")?
+            .to_string(),
+    };
+
+    let mut cfg = run(&["--print", "cfg", "--target", &target])?
+        .lines()
+        .map(|line| {
+            line.parse()
+                .with_context(|| format!("rustc printed an invalid cfg option {line:?}"))
+        })
+        .collect::<anyhow::Result<Vec<cargo_platform::Cfg>>>()?;
+    cfg.extend(extra_cfg);
+
+    Ok(Platform { target, cfg })
+}
+
+/// Find the closest `Cargo.toml` in the current directory or its parents,
+/// like cargo does.
+fn find_manifest() -> anyhow::Result<PathBuf> {
+    let cwd = std::env::current_dir().context("failed to get the current directory")?;
+
+    cwd.ancestors()
+        .map(|dir| dir.join("Cargo.toml"))
+        .find(|manifest| manifest.is_file())
+        .with_context(|| {
+            format!(
+                "could not find `Cargo.toml` in `{}` or any parent directory",
+                cwd.display()
+            )
+        })
+}
+
+/// Create the output file along with any missing parent directories.
+fn create_output(path: &Path) -> anyhow::Result<File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create directory {}", parent.display()))?;
     }
 
-    Ok(result)
+    File::create(path).with_context(|| format!("failed to create {}", path.display()))
 }
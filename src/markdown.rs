@@ -0,0 +1,91 @@
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+
+use itertools::Itertools;
+
+use crate::{
+    dedup_license_texts, read_license_texts, Column, DepTable, LicenseTable, LicenseTextOptions,
+};
+
+/// Pipes would otherwise end the table cell early.
+fn escape(cell: &str) -> String {
+    cell.replace('|', "\\|")
+}
+
+/// Pick a code fence longer than any run of backticks in the text so the
+/// license can't close the block early.
+fn fence_for(text: &[u8]) -> String {
+    let longest = text
+        .split(|&b| b != b'`')
+        .map(<[u8]>::len)
+        .max()
+        .unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn write_fenced(out: &mut impl Write, text: &[u8]) -> io::Result<()> {
+    let fence = fence_for(text);
+    writeln!(out, "\n{fence}text")?;
+    out.write_all(text)?;
+    if !text.ends_with(b"\n") {
+        out.write_all(b"\n")?;
+    }
+    writeln!(out, "{fence}")
+}
+
+pub fn write(
+    out: &mut impl Write,
+    depencies_list: &[DepTable<'_>],
+    licenses_list: &BTreeSet<LicenseTable>,
+    columns: &[Column],
+    text_options: LicenseTextOptions,
+) -> io::Result<()> {
+    let header = columns
+        .iter()
+        .map(|column| escape(column.header()))
+        .join(" | ");
+    let separator = columns
+        .iter()
+        .map(|column| "-".repeat(column.header().len()))
+        .join(" | ");
+    writeln!(out, "| {header} |")?;
+    writeln!(out, "| {separator} |")?;
+
+    for dep in depencies_list {
+        let row = columns
+            .iter()
+            .map(|column| escape(&column.cell(dep)))
+            .join(" | ");
+        writeln!(out, "| {row} |")?;
+    }
+
+    let license_texts = read_license_texts(licenses_list, text_options.max_bytes);
+
+    if text_options.dedup {
+        for (tables, buf) in dedup_license_texts(license_texts) {
+            let used_by = tables
+                .iter()
+                .map(|table| format!("{} {}", table.name, table.version))
+                .join(", ");
+
+            writeln!(out, "\n## License used by {}", escape(&used_by))?;
+            write_fenced(out, &buf)?;
+        }
+
+        return Ok(());
+    }
+
+    for (LicenseTable { name, version, .. }, texts) in license_texts {
+        if texts.is_empty() {
+            continue;
+        }
+
+        writeln!(out, "\n## {name} {version} licenses")?;
+
+        for (_, buf) in texts {
+            write_fenced(out, &buf)?;
+        }
+    }
+
+    Ok(())
+}
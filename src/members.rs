@@ -0,0 +1,77 @@
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+
+use cargo_metadata::{Metadata, PackageId};
+
+use crate::{BomOptions, DepTable, Kind, Kinds, TableStyle};
+
+/// List the direct dependencies of each workspace member under its name
+/// rather than one table of all crates, keeping who depends on what. Crates
+/// left out of the BOM are left out here too.
+pub fn write(
+    out: &mut impl Write,
+    metadata: &Metadata,
+    opts: &BomOptions,
+    list: &[DepTable<'_>],
+    style: TableStyle,
+) -> crate::Result<()> {
+    let resolve = metadata
+        .resolve
+        .as_ref()
+        .ok_or(crate::Error::NoDependencyGraph)?;
+    let kinds = Kind::expand(&opts.kinds);
+    let deps: HashMap<&PackageId, &DepTable> =
+        list.iter().map(|dep| (&dep.package.id, dep)).collect();
+
+    for (i, root) in crate::workspace_roots(metadata, &opts.packages)?
+        .into_iter()
+        .enumerate()
+    {
+        if i > 0 {
+            writeln!(out)?;
+        }
+        let member = &metadata[root];
+        writeln!(out, "{} {}", member.name, member.version)?;
+
+        let Some(node) = resolve.nodes.iter().find(|node| node.id == *root) else {
+            continue;
+        };
+
+        // The same crate may be a dependency of more than one kind
+        let mut direct: Vec<(&DepTable, Kinds)> = Vec::new();
+        for dep in &node.deps {
+            let Some(table) = deps.get(&dep.pkg) else {
+                continue;
+            };
+            let dep_kinds: BTreeSet<Kind> = dep
+                .dep_kinds
+                .iter()
+                .filter(|dep_kind| {
+                    opts.platform
+                        .as_ref()
+                        .is_none_or(|platform| platform.follows(dep_kind))
+                })
+                .filter_map(|dep_kind| Kind::from_dependency_kind(dep_kind.kind))
+                .filter(|kind| kinds.contains(kind))
+                .collect();
+            if !dep_kinds.is_empty() {
+                direct.push((table, Kinds(dep_kinds)));
+            }
+        }
+        direct.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut builder = tabled::builder::Builder::default();
+        builder.push_record(["Name", "Version", "Licenses", "Kind"]);
+        for (dep, dep_kinds) in &direct {
+            builder.push_record([
+                dep.name.clone(),
+                dep.version.clone(),
+                dep.licenses.to_string(),
+                dep_kinds.to_string(),
+            ]);
+        }
+        writeln!(out, "{}", crate::render_table(builder, style))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,326 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use cargo_metadata::{Metadata, Package, PackageId};
+use serde::Serialize;
+use time::format_description::well_known::Rfc3339;
+
+use crate::{BomOptions, DepTable, Kind, Licenses};
+
+/// The parts of an analyzer result of the [OSS Review
+/// Toolkit](https://oss-review-toolkit.org/) that describe the projects and
+/// their packages, as written by `ort analyze`.
+#[derive(Debug, Serialize)]
+struct OrtResult<'a> {
+    repository: Repository,
+    analyzer: AnalyzerRun<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct Repository {
+    vcs: VcsInfo,
+    vcs_processed: VcsInfo,
+    config: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+struct AnalyzerRun<'a> {
+    start_time: String,
+    end_time: String,
+    environment: Environment,
+    config: AnalyzerConfiguration,
+    result: AnalyzerResult<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct Environment {
+    ort_version: String,
+    java_version: &'static str,
+    os: &'static str,
+    processors: usize,
+    max_memory: u64,
+    variables: BTreeMap<String, String>,
+    tool_versions: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+struct AnalyzerConfiguration {
+    allow_dynamic_versions: bool,
+    skip_excluded: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct AnalyzerResult<'a> {
+    projects: Vec<Project<'a>>,
+    packages: Vec<OrtPackage<'a>>,
+    issues: BTreeMap<String, Vec<()>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Project<'a> {
+    id: String,
+    definition_file_path: String,
+    declared_licenses: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    declared_licenses_processed: Option<ProcessedLicenses>,
+    vcs: VcsInfo,
+    vcs_processed: VcsInfo,
+    homepage_url: &'a str,
+    scopes: Vec<Scope>,
+}
+
+#[derive(Debug, Serialize)]
+struct Scope {
+    name: &'static str,
+    dependencies: Vec<PackageReference>,
+}
+
+#[derive(Debug, Serialize)]
+struct PackageReference {
+    id: String,
+}
+
+#[derive(Debug, Serialize)]
+struct OrtPackage<'a> {
+    id: String,
+    purl: String,
+    authors: &'a [String],
+    declared_licenses: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    declared_licenses_processed: Option<ProcessedLicenses>,
+    description: &'a str,
+    homepage_url: &'a str,
+    binary_artifact: RemoteArtifact,
+    source_artifact: RemoteArtifact,
+    vcs: VcsInfo,
+    vcs_processed: VcsInfo,
+}
+
+#[derive(Debug, Serialize)]
+struct ProcessedLicenses {
+    spdx_expression: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct RemoteArtifact {
+    url: String,
+    hash: Hash,
+}
+
+/// `cargo metadata` doesn't tell the checksums of the crates, so hashes are
+/// left empty as ORT does for unknown ones.
+#[derive(Debug, Default, Serialize)]
+struct Hash {
+    value: String,
+    algorithm: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+struct VcsInfo {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    url: String,
+    revision: String,
+    path: String,
+}
+
+/// ORT identifiers are `type:namespace:name:version`, crates having no
+/// namespace.
+fn identifier(kind: &str, package: &Package) -> String {
+    format!("{kind}::{}:{}", package.name, package.version)
+}
+
+/// Where the source of a package is kept. Crates from git repositories are
+/// pinned to their commit, others only have the `repository` URL of their
+/// manifest, which is assumed to be git when hosted somewhere that only
+/// speaks git.
+fn vcs_info(package: &Package) -> VcsInfo {
+    if let Some(source) = package.source.as_ref() {
+        if let Some(url) = source.repr.strip_prefix("git+") {
+            let (url, revision) = url.rsplit_once('#').unwrap_or((url, ""));
+            let url = url.split_once('?').map_or(url, |(url, _)| url);
+            return VcsInfo {
+                kind: "Git",
+                url: url.to_string(),
+                revision: revision.to_string(),
+                path: String::new(),
+            };
+        }
+    }
+
+    let Some(ref url) = package.repository else {
+        return VcsInfo::default();
+    };
+    let is_git = url.ends_with(".git")
+        || ["github.com", "gitlab.com", "bitbucket.org", "codeberg.org"]
+            .iter()
+            .any(|host| url.contains(&format!("://{host}/")));
+    VcsInfo {
+        kind: if is_git { "Git" } else { "" },
+        url: url.clone(),
+        ..VcsInfo::default()
+    }
+}
+
+/// The download URL of a crates.io crate, which is how ORT fetches the
+/// source of Cargo packages.
+fn source_artifact(package: &Package) -> RemoteArtifact {
+    if !package
+        .source
+        .as_ref()
+        .is_some_and(|source| source.is_crates_io())
+    {
+        return RemoteArtifact::default();
+    }
+
+    RemoteArtifact {
+        url: format!(
+            "https://crates.io/api/v1/crates/{}/{}/download",
+            package.name, package.version
+        ),
+        hash: Hash::default(),
+    }
+}
+
+fn processed_licenses(licenses: &Licenses<'_>) -> Option<ProcessedLicenses> {
+    match licenses {
+        Licenses::List(expression) => Some(ProcessedLicenses {
+            spdx_expression: expression.canonical(),
+        }),
+        _ => None,
+    }
+}
+
+impl<'a> From<&'a DepTable<'a>> for OrtPackage<'a> {
+    fn from(dep: &'a DepTable<'a>) -> Self {
+        let package = dep.package;
+        let vcs = vcs_info(package);
+        OrtPackage {
+            id: identifier("Crate", package),
+            purl: crate::purl(package),
+            authors: &dep.authors,
+            declared_licenses: package.license.as_deref().into_iter().collect(),
+            declared_licenses_processed: processed_licenses(&dep.licenses),
+            description: package.description.as_deref().unwrap_or_default(),
+            homepage_url: package.homepage.as_deref().unwrap_or_default(),
+            binary_artifact: RemoteArtifact::default(),
+            source_artifact: source_artifact(package),
+            vcs_processed: vcs.clone(),
+            vcs,
+        }
+    }
+}
+
+/// Write an ORT analyzer result with the workspace members as projects and
+/// the dependencies as packages, to be picked up by `ort evaluate` or `ort
+/// report`. Each project has a scope per kind with the crates of the BOM
+/// its own dependency graph reaches.
+pub fn write(
+    out: &mut impl Write,
+    metadatas: &[Metadata],
+    opts: &BomOptions,
+    list: &[DepTable<'_>],
+) -> crate::Result<()> {
+    let time = crate::spdx_doc::creation_time()?
+        .format(&Rfc3339)
+        .map_err(io::Error::other)?;
+
+    // The kind a crate is reached through may differ between members
+    let kinds = Kind::expand(&opts.kinds);
+    let scopes = |metadata: &Metadata, root: &PackageId| -> crate::Result<Vec<Scope>> {
+        let resolved = crate::resolve::dependencies(
+            metadata,
+            &[root],
+            &kinds,
+            opts.depth,
+            false,
+            opts.platform.as_ref(),
+        )?;
+
+        Ok([
+            (Kind::Normal, "dependencies"),
+            (Kind::Dev, "dev-dependencies"),
+            (Kind::Build, "build-dependencies"),
+        ]
+        .into_iter()
+        .map(|(kind, name)| Scope {
+            name,
+            dependencies: list
+                .iter()
+                .filter(|dep| {
+                    resolved
+                        .get(&dep.package.id)
+                        .is_some_and(|resolved| resolved.kinds.0.contains(&kind))
+                })
+                .map(|dep| PackageReference {
+                    id: identifier("Crate", dep.package),
+                })
+                .collect(),
+        })
+        .filter(|scope| !scope.dependencies.is_empty())
+        .collect())
+    };
+
+    let mut projects = Vec::new();
+    for metadata in metadatas {
+        for root in crate::workspace_roots(metadata, &opts.packages)? {
+            let package = &metadata[root];
+            let vcs = vcs_info(package);
+            projects.push(Project {
+                id: identifier("Cargo", package),
+                // Relative to the root of the workspace
+                definition_file_path: package
+                    .manifest_path
+                    .strip_prefix(&metadata.workspace_root)
+                    .unwrap_or(&package.manifest_path)
+                    .to_string(),
+                declared_licenses: package.license.as_deref().into_iter().collect(),
+                declared_licenses_processed: processed_licenses(&crate::package_licenses(package)),
+                vcs_processed: vcs.clone(),
+                vcs,
+                homepage_url: package.homepage.as_deref().unwrap_or_default(),
+                scopes: scopes(metadata, root)?,
+            });
+        }
+    }
+
+    let result = OrtResult {
+        repository: Repository {
+            vcs: VcsInfo::default(),
+            vcs_processed: VcsInfo::default(),
+            config: BTreeMap::new(),
+        },
+        analyzer: AnalyzerRun {
+            start_time: time.clone(),
+            end_time: time,
+            environment: Environment {
+                ort_version: format!("cargo-bom-{}", env!("CARGO_PKG_VERSION")),
+                java_version: "",
+                os: std::env::consts::OS,
+                // Unknown like the memory, which keeps the output reproducible
+                processors: 0,
+                max_memory: 0,
+                variables: BTreeMap::new(),
+                tool_versions: BTreeMap::new(),
+            },
+            config: AnalyzerConfiguration {
+                allow_dynamic_versions: false,
+                skip_excluded: false,
+            },
+            result: AnalyzerResult {
+                projects,
+                // Workspace members are the projects, not packages of them
+                packages: list
+                    .iter()
+                    .filter(|dep| !dep.member)
+                    .map(OrtPackage::from)
+                    .collect(),
+                issues: BTreeMap::new(),
+            },
+        },
+    };
+
+    serde_json::to_writer_pretty(&mut *out, &result).map_err(io::Error::from)?;
+    out.write_all(b"\n")?;
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use spdx::{LicenseItem, LicenseReq, Licensee};
+
+use crate::{DepTable, Licenses};
+
+/// License policy built from `--allow` and `--deny`.
+#[derive(Debug, Default)]
+pub struct Policy {
+    pub allow: Vec<Licensee>,
+    pub deny: Vec<Licensee>,
+}
+
+impl Policy {
+    fn allows(&self, req: &LicenseReq) -> bool {
+        let allowed = self.allow.is_empty() || self.allow.iter().any(|l| l.satisfies(req));
+        let denied = self.deny.iter().any(|l| l.satisfies(req));
+        allowed && !denied
+    }
+
+    /// Check whether the licenses of a crate satisfy the policy. The SPDX
+    /// expression is evaluated as a whole, so a dual-licensed crate passes if
+    /// any of its license options is allowed.
+    ///
+    /// Crates without a valid SPDX expression or a license detected from
+    /// their license file can only pass a policy without an allowlist.
+    pub fn check(&self, licenses: &Licenses<'_>) -> bool {
+        match *licenses {
+            Licenses::List(ref expression) => expression.0.evaluate(|req| self.allows(req)),
+            Licenses::File(_, Some(ref detected)) => self.allows(&detected.license.into()),
+            Licenses::Invalid(_) | Licenses::File(_, None) | Licenses::Missing => {
+                self.allow.is_empty()
+            }
+        }
+    }
+
+    /// Dependencies violating the policy.
+    pub fn violations<'a, 'b>(&self, depencies_list: &'a [DepTable<'b>]) -> Vec<&'a DepTable<'b>> {
+        depencies_list
+            .iter()
+            .filter(|dep| !self.check(&dep.licenses))
+            .collect()
+    }
+}
+
+/// Whether a crate can be used under a license approved by the Open Source
+/// Initiative, according to the SPDX license list. Like with the policy, a
+/// dual-licensed crate only needs one of its options to be approved.
+pub fn osi_approved(licenses: &Licenses<'_>) -> bool {
+    let approved = |req: &LicenseReq| match req.license {
+        LicenseItem::Spdx { id, .. } => id.is_osi_approved(),
+        LicenseItem::Other { .. } => false,
+    };
+
+    match *licenses {
+        Licenses::List(ref expression) => expression.0.evaluate(approved),
+        Licenses::File(_, Some(ref detected)) => detected.license.is_osi_approved(),
+        Licenses::Invalid(_) | Licenses::File(_, None) | Licenses::Missing => false,
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use cargo_metadata::{camino::Utf8Path, Source};
+use serde::Deserialize;
+
+/// The parts of a cargo configuration file naming registries.
+#[derive(Debug, Default, Deserialize)]
+struct CargoConfig {
+    #[serde(default)]
+    registries: BTreeMap<String, RegistryConfig>,
+    #[serde(default)]
+    source: BTreeMap<String, SourceConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RegistryConfig {
+    index: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SourceConfig {
+    registry: Option<String>,
+}
+
+/// Names of the alternative registries and source replacements configured
+/// in `.cargo/config.toml`, by the source they show up as in `cargo
+/// metadata`.
+#[derive(Debug, Default)]
+pub struct Registries(HashMap<String, String>);
+
+impl Registries {
+    /// Read the configuration files cargo would use for the workspace: the
+    /// one in `CARGO_HOME` and those in the workspace root and its parent
+    /// directories, the closest one taking precedence.
+    pub fn load(workspace_root: &Utf8Path) -> Registries {
+        let mut dirs: Vec<PathBuf> = workspace_root
+            .ancestors()
+            .map(|dir| dir.join(".cargo").into_std_path_buf())
+            .collect();
+        if let Some(home) = cargo_home() {
+            dirs.push(home);
+        }
+
+        let mut registries = Registries::default();
+        for dir in dirs.iter().rev() {
+            for file in ["config", "config.toml"] {
+                // Cargo has already rejected broken configuration files
+                let Ok(text) = fs::read_to_string(dir.join(file)) else {
+                    continue;
+                };
+                let Ok(config) = ::toml::from_str::<CargoConfig>(&text) else {
+                    continue;
+                };
+                registries.add(config);
+            }
+        }
+
+        registries
+    }
+
+    fn add(&mut self, config: CargoConfig) {
+        let registries = config
+            .registries
+            .into_iter()
+            .filter_map(|(name, registry)| Some((name, registry.index?)));
+        let sources = config
+            .source
+            .into_iter()
+            .filter_map(|(name, source)| Some((name, source.registry?)));
+
+        for (name, index) in registries.chain(sources) {
+            self.0.insert(source_repr(&index), name);
+        }
+    }
+
+    /// Configured name of the registry a package comes from.
+    pub fn name(&self, source: &Source) -> Option<&str> {
+        self.0
+            .get(source.repr.trim_end_matches('/'))
+            .map(String::as_str)
+    }
+}
+
+/// How a registry index URL appears as a package source: sparse indexes
+/// keep their `sparse+` prefix and git indexes get `registry+`.
+fn source_repr(index: &str) -> String {
+    let index = index.trim_end_matches('/');
+    if index.starts_with("sparse+") {
+        index.to_string()
+    } else {
+        format!("registry+{index}")
+    }
+}
+
+pub(crate) fn cargo_home() -> Option<PathBuf> {
+    if let Some(home) = env::var_os("CARGO_HOME") {
+        return Some(PathBuf::from(home));
+    }
+
+    let home = env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" })?;
+    Some(PathBuf::from(home).join(".cargo"))
+}
@@ -0,0 +1,198 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+use cargo_metadata::{DepKindInfo, DependencyKind, Metadata, Node, Package, PackageId};
+
+use crate::{Kind, Kinds};
+
+/// A package in the dependency graph.
+#[derive(Debug)]
+pub struct Resolved<'a> {
+    pub package: &'a Package,
+    /// Features enabled for the package
+    pub features: &'a [String],
+    pub kinds: Kinds,
+    /// Whether a workspace member depends on the package directly
+    pub direct: bool,
+    /// Whether the package is a member of the workspace itself
+    pub member: bool,
+    /// Whether every path to the package goes through an `optional = true`
+    /// dependency, so that it could be dropped by disabling features
+    pub optional: bool,
+}
+
+/// The platform to follow the platform-specific dependencies of, such as
+/// `[target.'cfg(unix)'.dependencies]`.
+#[derive(Debug)]
+pub struct Platform {
+    /// Target triple
+    pub target: String,
+    /// Active `cfg` options, as printed by `rustc --print cfg` along with
+    /// any extra `--cfg`
+    pub cfg: Vec<cargo_platform::Cfg>,
+}
+
+impl Platform {
+    /// Whether an edge of the dependency graph is active on the platform.
+    pub fn follows(&self, dep_kind: &DepKindInfo) -> bool {
+        dep_kind
+            .target
+            .as_ref()
+            .is_none_or(|target| target.matches(&self.target, &self.cfg))
+    }
+}
+
+/// Walk the resolved dependency graph starting from the given workspace
+/// members.
+///
+/// The first hop from a root follows the requested `kinds`.
+/// Beyond that only normal dependencies are followed, plus build
+/// dependencies when those were requested, since dev-dependencies of
+/// dependencies are never built. A crate reached through a build dependency
+/// anywhere along the way is attributed to the `build` kind, otherwise it
+/// inherits the kind of the first hop.
+///
+/// Workspace members are only listed themselves with `include_members`,
+/// along with the roots. The dependencies of members reached from the roots
+/// are followed either way.
+///
+/// `depth` limits how many hops away from a root are followed;
+/// `Some(1)` only lists direct dependencies. With a `platform` only the
+/// dependencies active on it are followed.
+///
+/// An edge is optional when the depending package declares the dependency
+/// with `optional = true`, and everything reached through such an edge is
+/// optional unless there's also a path to it without one.
+pub fn dependencies<'a>(
+    metadata: &'a Metadata,
+    roots: &[&'a PackageId],
+    kinds: &BTreeSet<Kind>,
+    depth: Option<u32>,
+    include_members: bool,
+    platform: Option<&Platform>,
+) -> crate::Result<BTreeMap<&'a PackageId, Resolved<'a>>> {
+    let follows =
+        |dep_kind: &DepKindInfo| platform.is_none_or(|platform| platform.follows(dep_kind));
+
+    let resolve = metadata
+        .resolve
+        .as_ref()
+        .ok_or(crate::Error::NoDependencyGraph)?;
+
+    let nodes: HashMap<&PackageId, &Node> = resolve.nodes.iter().map(|n| (&n.id, n)).collect();
+    let packages: HashMap<&PackageId, &Package> =
+        metadata.packages.iter().map(|p| (&p.id, p)).collect();
+    let members: HashSet<&PackageId> = metadata.workspace_members.iter().collect();
+
+    let mut queue = VecDeque::new();
+    for root in roots {
+        let Some(node) = nodes.get(root) else {
+            continue;
+        };
+
+        for dep in &node.deps {
+            for dep_kind in dep.dep_kinds.iter().filter(|dep_kind| follows(dep_kind)) {
+                if let Some(kind) = Kind::from_dependency_kind(dep_kind.kind) {
+                    if kinds.contains(&kind) {
+                        let optional = is_optional(&packages, root, &dep.pkg, dep_kind.kind);
+                        queue.push_back((&dep.pkg, kind, 1, true, optional));
+                    }
+                }
+            }
+        }
+    }
+
+    let follow_build = kinds.contains(&Kind::Build);
+    let mut visited = HashSet::new();
+    let mut result: BTreeMap<&PackageId, Resolved> = BTreeMap::new();
+
+    if include_members {
+        for root in roots {
+            if let Some(package) = packages.get(root) {
+                result.insert(
+                    root,
+                    Resolved {
+                        package,
+                        features: nodes.get(root).map_or(&[], |node| &node.features),
+                        kinds: Kinds::default(),
+                        direct: false,
+                        member: true,
+                        optional: false,
+                    },
+                );
+            }
+        }
+    }
+
+    while let Some((id, kind, level, direct, optional)) = queue.pop_front() {
+        // A crate may be reached both directly through a member reached
+        // from a root and transitively before that, and both through
+        // optional dependencies and without them
+        if !visited.insert((id, kind, direct, optional)) {
+            continue;
+        }
+
+        // Crates in the workspace aren't dependencies of it, but their own
+        // dependencies are still followed
+        let member = members.contains(id);
+        let package = packages.get(id).filter(|_| include_members || !member);
+        if let Some(package) = package {
+            let resolved = result.entry(id).or_insert_with(|| Resolved {
+                package,
+                features: nodes.get(id).map_or(&[], |node| &node.features),
+                kinds: Kinds::default(),
+                direct: false,
+                member,
+                optional: true,
+            });
+            resolved.kinds.0.insert(kind);
+            resolved.direct |= direct;
+            resolved.optional &= optional;
+        }
+
+        if depth.is_some_and(|depth| level >= depth) {
+            continue;
+        }
+
+        let Some(node) = nodes.get(id) else {
+            continue;
+        };
+
+        for dep in &node.deps {
+            for dep_kind in dep.dep_kinds.iter().filter(|dep_kind| follows(dep_kind)) {
+                let optional = optional || is_optional(&packages, id, &dep.pkg, dep_kind.kind);
+                match Kind::from_dependency_kind(dep_kind.kind) {
+                    Some(Kind::Normal) => {
+                        queue.push_back((&dep.pkg, kind, level + 1, member, optional))
+                    }
+                    Some(Kind::Build) if follow_build => {
+                        queue.push_back((&dep.pkg, Kind::Build, level + 1, member, optional))
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Whether `from` declares its dependency of the given kind on `to` as
+/// optional. A crate may be declared more than once, for different targets,
+/// and the edge is only optional when all of those are.
+fn is_optional(
+    packages: &HashMap<&PackageId, &Package>,
+    from: &PackageId,
+    to: &PackageId,
+    kind: DependencyKind,
+) -> bool {
+    let (Some(from), Some(to)) = (packages.get(from), packages.get(to)) else {
+        return false;
+    };
+
+    let mut declared = from
+        .dependencies
+        .iter()
+        .filter(|dep| dep.name == to.name && dep.kind == kind)
+        .peekable();
+    declared.peek().is_some() && declared.all(|dep| dep.optional)
+}
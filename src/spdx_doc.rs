@@ -0,0 +1,207 @@
+use std::io::{self, Write};
+
+use cargo_metadata::Package;
+use time::OffsetDateTime;
+
+use crate::{DepTable, Licenses};
+
+const SPDX_VERSION: &str = "SPDX-2.3";
+const DATA_LICENSE: &str = "CC0-1.0";
+
+/// SPDX identifiers may only contain letters, numbers, `.` and `-`.
+fn sanitize_id(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Creation time of the document. Honors `SOURCE_DATE_EPOCH` so that the
+/// output can be reproduced byte for byte.
+pub(crate) fn creation_time() -> io::Result<OffsetDateTime> {
+    let Ok(epoch) = std::env::var("SOURCE_DATE_EPOCH") else {
+        return Ok(OffsetDateTime::now_utc());
+    };
+
+    let seconds = epoch.trim().parse::<i64>().map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid SOURCE_DATE_EPOCH {epoch:?}: {err}"),
+        )
+    })?;
+    OffsetDateTime::from_unix_timestamp(seconds).map_err(io::Error::other)
+}
+
+fn package_spdx_id(package: &Package) -> String {
+    sanitize_id(&format!(
+        "SPDXRef-Package-{}-{}",
+        package.name, package.version
+    ))
+}
+
+/// License reference used for crates that only ship a license file.
+fn license_ref(package: &Package) -> Option<String> {
+    package.license_file().map(|file| {
+        let file_name = file.file_name().unwrap_or(file.as_str());
+        sanitize_id(&format!(
+            "LicenseRef-{}-{}-{}",
+            package.name, package.version, file_name
+        ))
+    })
+}
+
+/// Use the declared license in its canonical SPDX form, fall back to a
+/// `LicenseRef-` for a license file and `NOASSERTION` when nothing is known
+/// or the `license` field isn't an SPDX expression at all.
+fn package_license(package: &Package) -> String {
+    match crate::package_licenses(package) {
+        Licenses::List(expression) => expression.canonical(),
+        Licenses::File(..) => license_ref(package).unwrap_or_else(|| "NOASSERTION".to_string()),
+        Licenses::Invalid(_) | Licenses::Missing => "NOASSERTION".to_string(),
+    }
+}
+
+fn write_package(out: &mut impl Write, package: &Package) -> io::Result<()> {
+    let license = package_license(package);
+
+    writeln!(out)?;
+    writeln!(out, "PackageName: {}", package.name)?;
+    writeln!(out, "SPDXID: {}", package_spdx_id(package))?;
+    writeln!(out, "PackageVersion: {}", package.version)?;
+    writeln!(out, "PackageDownloadLocation: NOASSERTION")?;
+    writeln!(out, "FilesAnalyzed: false")?;
+    writeln!(out, "PackageLicenseConcluded: {license}")?;
+    writeln!(out, "PackageLicenseDeclared: {license}")?;
+    if let Licenses::Invalid(declared) = crate::package_licenses(package) {
+        writeln!(
+            out,
+            "PackageLicenseComments: <text>Declared license: {declared}</text>"
+        )?;
+    }
+    writeln!(out, "PackageCopyrightText: NOASSERTION")?;
+    writeln!(
+        out,
+        "ExternalRef: PACKAGE-MANAGER purl {}",
+        crate::purl(package)
+    )?;
+
+    Ok(())
+}
+
+fn write_extracted_license(out: &mut impl Write, package: &Package) -> io::Result<()> {
+    let Some(license_ref) = license_ref(package) else {
+        return Ok(());
+    };
+
+    // Like the other license texts, a file that can't be read doesn't fail
+    // the whole document
+    let text = match crate::declared_license_file(package) {
+        Ok(Some(file)) => match std::fs::read_to_string(&file) {
+            Ok(text) => text.replace("\r\n", "\n"),
+            Err(err) => {
+                eprintln!(
+                    "warning: failed to read license file of {} {}: {file}: {err}",
+                    package.name, package.version
+                );
+                String::new()
+            }
+        },
+        _ => String::new(),
+    };
+
+    writeln!(out)?;
+    writeln!(out, "LicenseID: {license_ref}")?;
+    writeln!(out, "ExtractedText: <text>{text}</text>")?;
+
+    Ok(())
+}
+
+/// Write the SPDX document, describing the `root` package if there's one
+/// and the workspace otherwise.
+pub fn write(
+    out: &mut impl Write,
+    root: Option<&Package>,
+    list: &[DepTable<'_>],
+) -> io::Result<()> {
+    // The root package is written first, also when it's in the list with
+    // `--include-workspace-members`
+    let packages: Vec<&Package> = list
+        .iter()
+        .map(|dep| dep.package)
+        .filter(|package| root.is_none_or(|root| root.id != package.id))
+        .collect();
+
+    let now = creation_time()?;
+    let created = now
+        .format(time::macros::format_description!(
+            "[year]-[month]-[day]T[hour]:[minute]:[second]Z"
+        ))
+        .map_err(io::Error::other)?;
+
+    let document_name = match root {
+        Some(package) => format!("{}-{}", package.name, package.version),
+        None => "workspace".to_string(),
+    };
+
+    writeln!(out, "SPDXVersion: {SPDX_VERSION}")?;
+    writeln!(out, "DataLicense: {DATA_LICENSE}")?;
+    writeln!(out, "SPDXID: SPDXRef-DOCUMENT")?;
+    writeln!(out, "DocumentName: {document_name}")?;
+    writeln!(
+        out,
+        "DocumentNamespace: https://spdx.org/spdxdocs/{}-{}",
+        sanitize_id(&document_name),
+        now.unix_timestamp_nanos()
+    )?;
+    writeln!(
+        out,
+        "Creator: Tool: cargo-bom-{}",
+        env!("CARGO_PKG_VERSION")
+    )?;
+    writeln!(out, "Created: {created}")?;
+
+    if let Some(root) = root {
+        write_package(out, root)?;
+    }
+
+    for package in &packages {
+        write_package(out, package)?;
+    }
+
+    writeln!(out)?;
+    match root {
+        Some(root) => {
+            let root_id = package_spdx_id(root);
+            writeln!(out, "Relationship: SPDXRef-DOCUMENT DESCRIBES {root_id}")?;
+            for package in &packages {
+                writeln!(
+                    out,
+                    "Relationship: {root_id} DEPENDS_ON {}",
+                    package_spdx_id(package)
+                )?;
+            }
+        }
+        None => {
+            for package in &packages {
+                writeln!(
+                    out,
+                    "Relationship: SPDXRef-DOCUMENT DESCRIBES {}",
+                    package_spdx_id(package)
+                )?;
+            }
+        }
+    }
+
+    for package in root.into_iter().chain(packages.iter().copied()) {
+        if package.license.is_none() {
+            write_extracted_license(out, package)?;
+        }
+    }
+
+    Ok(())
+}
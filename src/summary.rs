@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use itertools::Itertools;
+
+use crate::{DepTable, Licenses, TableStyle};
+
+/// Names of the individual licenses of a crate. A dual-licensed crate is
+/// counted under each of its licenses.
+fn license_names(licenses: &Licenses<'_>) -> Vec<String> {
+    match licenses {
+        Licenses::List(expression) => expression
+            .requirements()
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+        _ => vec![licenses.to_string()],
+    }
+}
+
+/// Number of distinct SPDX licenses declared by or detected for the crates.
+pub fn distinct_licenses(list: &[DepTable<'_>]) -> usize {
+    list.iter()
+        .flat_map(|dep| match dep.licenses {
+            Licenses::List(ref expression) => expression
+                .requirements()
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            Licenses::File(_, Some(ref detected)) => vec![detected.license.name.to_string()],
+            Licenses::Invalid(_) | Licenses::File(_, None) | Licenses::Missing => Vec::new(),
+        })
+        .unique()
+        .count()
+}
+
+pub fn write(out: &mut impl Write, list: &[DepTable<'_>], style: TableStyle) -> io::Result<()> {
+    let mut groups: BTreeMap<String, Vec<&DepTable>> = BTreeMap::new();
+    for dep in list {
+        for license in license_names(&dep.licenses) {
+            groups.entry(license).or_default().push(dep);
+        }
+    }
+
+    // List crates alphabetically regardless of `--sort-by`
+    for deps in groups.values_mut() {
+        deps.sort();
+    }
+
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record(["License", "Count", "Crates"]);
+    for (license, deps) in &groups {
+        let crates = deps
+            .iter()
+            .map(|dep| format!("{} {}", dep.name, dep.version))
+            .join("\n");
+        builder.push_record([license.clone(), deps.len().to_string(), crates]);
+    }
+
+    writeln!(out, "{}", crate::render_table(builder, style))?;
+    writeln!(
+        out,
+        "\n{} crates, {} distinct licenses",
+        list.len(),
+        groups.len()
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+
+use handlebars::Handlebars;
+use serde::Serialize;
+
+use crate::json::{Dependency, BOM_VERSION};
+use crate::{read_license_texts, DepTable, Error, LicenseTable};
+
+#[derive(Debug, Serialize)]
+struct Bom<'a> {
+    bom_version: u32,
+    dependencies: Vec<Entry<'a>>,
+}
+
+/// The fields of a dependency in `json` output along with its license
+/// texts, so templates can render notices too.
+#[derive(Debug, Serialize)]
+struct Entry<'a> {
+    #[serde(flatten)]
+    dependency: Dependency<'a>,
+    license_texts: Vec<LicenseText>,
+}
+
+#[derive(Debug, Serialize)]
+struct LicenseText {
+    /// Name of the file
+    file: String,
+    text: String,
+}
+
+/// Render the dependencies with a Handlebars template. The template sees
+/// the same `bom_version` and `dependencies` as `json` output, each
+/// dependency having a `license_texts` array of `file` and `text`. Nothing
+/// is HTML-escaped, and referring to a field that doesn't exist is an error
+/// rather than rendering nothing.
+pub fn write(
+    out: &mut impl Write,
+    template: &str,
+    depencies_list: &[DepTable<'_>],
+    licenses_list: &BTreeSet<LicenseTable>,
+    max_license_bytes: Option<u64>,
+) -> crate::Result<()> {
+    let mut handlebars = Handlebars::new();
+    handlebars.set_strict_mode(true);
+    handlebars.register_escape_fn(handlebars::no_escape);
+    handlebars
+        .register_template_string("bom", template)
+        .map_err(|err| Error::InvalidTemplate(Box::new(err)))?;
+
+    let mut texts: HashMap<(&str, &str), Vec<LicenseText>> =
+        read_license_texts(licenses_list, max_license_bytes)
+            .into_iter()
+            .map(|(table, files)| {
+                let texts = files
+                    .into_iter()
+                    .map(|(file, buf)| LicenseText {
+                        file: file.file_name().unwrap_or(file.as_str()).to_string(),
+                        text: String::from_utf8_lossy(&buf).into_owned(),
+                    })
+                    .collect();
+                ((table.name.as_str(), table.version.as_str()), texts)
+            })
+            .collect();
+
+    let bom = Bom {
+        bom_version: BOM_VERSION,
+        dependencies: depencies_list
+            .iter()
+            .map(|dep| Entry {
+                dependency: Dependency::from(dep),
+                license_texts: texts
+                    .remove(&(dep.name.as_str(), dep.version.as_str()))
+                    .unwrap_or_default(),
+            })
+            .collect(),
+    };
+
+    handlebars
+        .render_to_write("bom", &bom, out)
+        .map_err(|err| Error::RenderTemplate(Box::new(err)))
+}
@@ -0,0 +1,47 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::{DepTable, Licenses};
+
+#[derive(Debug, Serialize)]
+struct Bom<'a> {
+    dependencies: Vec<Dependency<'a>>,
+}
+
+/// Fields are serialized in declaration order, which keeps diffs between
+/// releases small.
+#[derive(Debug, Serialize)]
+struct Dependency<'a> {
+    name: &'a str,
+    version: &'a str,
+    licenses: Vec<String>,
+}
+
+impl<'a> From<&'a DepTable<'a>> for Dependency<'a> {
+    fn from(dep: &'a DepTable<'a>) -> Self {
+        let licenses = match dep.licenses {
+            Licenses::List(ref expression) => expression
+                .requirements()
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            ref licenses => vec![licenses.to_string()],
+        };
+
+        Dependency {
+            name: &dep.name,
+            version: &dep.version,
+            licenses,
+        }
+    }
+}
+
+pub fn write(out: &mut impl Write, list: &[DepTable<'_>]) -> io::Result<()> {
+    let bom = Bom {
+        dependencies: list.iter().map(Dependency::from).collect(),
+    };
+
+    let document = ::toml::to_string(&bom).map_err(io::Error::other)?;
+    out.write_all(document.as_bytes())
+}
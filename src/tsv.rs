@@ -0,0 +1,36 @@
+use std::io::{self, Write};
+
+use itertools::Itertools;
+
+use crate::{Column, DepTable};
+
+/// Tabs and line breaks would split a field, so they're turned into spaces.
+/// Nothing is quoted.
+fn field(text: &str) -> String {
+    text.replace(['\t', '\r', '\n'], " ")
+}
+
+pub fn write(
+    out: &mut impl Write,
+    depencies_list: &[DepTable<'_>],
+    headers: bool,
+    columns: &[Column],
+) -> io::Result<()> {
+    if headers {
+        let header = columns
+            .iter()
+            .map(|column| column.header().to_lowercase())
+            .join("\t");
+        writeln!(out, "{header}")?;
+    }
+
+    for dep in depencies_list {
+        let row = columns
+            .iter()
+            .map(|column| field(&column.cell(dep)))
+            .join("\t");
+        writeln!(out, "{row}")?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use std::fs;
+use std::path::PathBuf;
+
+use cargo_metadata::Package;
+use serde::Deserialize;
+
+/// An entry of the registry index, one per published version.
+#[derive(Debug, Deserialize)]
+struct IndexEntry {
+    vers: String,
+    yanked: bool,
+}
+
+/// The copy of the crates.io index cargo keeps in `CARGO_HOME`. Nothing is
+/// downloaded, so whether a crate has been yanked is only as fresh as cargo's
+/// last update of the index.
+#[derive(Debug)]
+pub struct Index {
+    /// `.cache` directories of the crates.io index, sparse or git
+    dirs: Vec<PathBuf>,
+}
+
+impl Index {
+    pub fn open() -> Index {
+        let dirs = crate::registries::cargo_home()
+            .map(|home| home.join("registry").join("index"))
+            .and_then(|index| index.read_dir().ok())
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                name.starts_with("index.crates.io-") || name.starts_with("github.com-")
+            })
+            .map(|entry| entry.path().join(".cache"))
+            .collect();
+
+        Index { dirs }
+    }
+
+    /// Whether the version of a crates.io package has been yanked, `None`
+    /// for packages from elsewhere or not found in the cached index.
+    pub fn is_yanked(&self, package: &Package) -> Option<bool> {
+        if !package.source.as_ref()?.is_crates_io() {
+            return None;
+        }
+
+        let path = index_path(&package.name);
+        let version = package.version.to_string();
+        self.dirs.iter().find_map(|dir| {
+            let data = fs::read(dir.join(&path)).ok()?;
+            // The cache file is a header followed by NUL-separated pairs of
+            // version and JSON entry
+            data.split(|&b| b == 0)
+                .filter_map(|entry| serde_json::from_slice::<IndexEntry>(entry).ok())
+                .find(|entry| entry.vers == version)
+                .map(|entry| entry.yanked)
+        })
+    }
+}
+
+/// Path of a crate within the index, such as `it/er/itertools` or
+/// `3/s/syn`.
+fn index_path(name: &str) -> PathBuf {
+    let name = name.to_lowercase();
+    match name.len() {
+        1 => ["1", &name].iter().collect(),
+        2 => ["2", &name].iter().collect(),
+        3 => ["3", &name[..1], &name].iter().collect(),
+        _ => [&name[..2], &name[2..4], &name].iter().collect(),
+    }
+}
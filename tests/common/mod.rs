@@ -0,0 +1,79 @@
+//! Fixture workspaces for running `cargo bom` against. Each fixture under
+//! `tests/fixtures` is copied into a temporary directory first, so tests can
+//! change it and cargo's `Cargo.lock` doesn't end up in the source tree.
+
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use tempfile::TempDir;
+
+pub struct Fixture {
+    dir: TempDir,
+}
+
+impl Fixture {
+    /// Copy `tests/fixtures/<name>` into a temporary directory.
+    pub fn new(name: &str) -> Fixture {
+        let source = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join(name);
+        let dir = tempfile::tempdir().expect("create temporary directory");
+        copy_dir(&source, dir.path());
+        Fixture { dir }
+    }
+
+    pub fn path(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.dir.path().join(relative)
+    }
+
+    /// Run `cargo bom` with `args` in the `app` crate of the fixture.
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.run_in("app", args)
+    }
+
+    /// Run `cargo bom` with `args` in a directory of the fixture. Nothing is
+    /// fetched, so fixtures only use path dependencies.
+    pub fn run_in(&self, dir: &str, args: &[&str]) -> Output {
+        self.command(dir)
+            .args(args)
+            .output()
+            .expect("run cargo-bom")
+    }
+
+    /// `cargo bom --offline` in a directory of the fixture, for tests that
+    /// need to set up more of the environment.
+    pub fn command(&self, dir: &str) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_cargo-bom"));
+        command
+            .args(["bom", "--offline"])
+            .current_dir(self.path(dir))
+            .env("SOURCE_DATE_EPOCH", "0")
+            .env_remove("GITHUB_ACTIONS");
+        command
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).expect("create fixture directory");
+    for entry in fs::read_dir(from).expect("read fixture directory") {
+        let entry = entry.expect("read fixture directory entry");
+        let target = to.join(entry.file_name());
+        if entry.file_type().expect("file type").is_dir() {
+            copy_dir(&entry.path(), &target);
+        } else {
+            fs::copy(entry.path(), &target).expect("copy fixture file");
+        }
+    }
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).expect("UTF-8 output")
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).expect("UTF-8 output")
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use std::fs;
+
+use common::{stderr, stdout, Fixture};
+use serde_json::{json, Value};
+
+fn component_licenses(bom: &Value, name: &str) -> Value {
+    bom["components"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|component| component["name"] == name)
+        .unwrap_or_else(|| panic!("no component {name}"))["licenses"]
+        .clone()
+}
+
+#[test]
+fn compound_licenses_are_expressions() {
+    let fixture = Fixture::new("legacy-licenses");
+    let output = fixture.run(&["--include-path-dependencies", "--format", "cyclonedx"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let bom: Value = serde_json::from_str(&stdout(&output)).unwrap();
+
+    assert_eq!(
+        component_licenses(&bom, "slash"),
+        json!([{ "expression": "MIT OR Apache-2.0" }])
+    );
+    assert_eq!(
+        component_licenses(&bom, "deprecated"),
+        json!([{ "expression": "GPL-2.0-or-later AND MIT" }])
+    );
+    assert_eq!(component_licenses(&bom, "freeform"), Value::Null);
+}
+
+#[test]
+fn single_license_is_an_id() {
+    let fixture = Fixture::new("legacy-licenses");
+    let manifest = fixture.path("slash/Cargo.toml");
+    let contents = fs::read_to_string(&manifest).unwrap();
+    fs::write(&manifest, contents.replace("MIT/Apache-2.0", "MIT")).unwrap();
+
+    let output = fixture.run(&["--include-path-dependencies", "--format", "cyclonedx"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let bom: Value = serde_json::from_str(&stdout(&output)).unwrap();
+
+    assert_eq!(
+        component_licenses(&bom, "slash"),
+        json!([{ "license": { "id": "MIT" } }])
+    );
+}
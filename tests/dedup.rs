@@ -0,0 +1,127 @@
+mod common;
+
+use common::{stderr, stdout, Fixture};
+use serde_json::Value;
+
+fn run(fixture: &Fixture, args: &[&str]) -> String {
+    let output = fixture.run(
+        &[
+            &["--include-path-dependencies", "--dedup-strategy", "name"],
+            args,
+        ]
+        .concat(),
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    stdout(&output)
+}
+
+#[test]
+fn table_collapses_versions() {
+    let fixture = Fixture::new("versions");
+    assert!(run(&fixture, &[]).contains("│ dup  │ 1.0.0, 2.0.0 │ MIT      │"));
+
+    let bom: Value = serde_json::from_str(&run(&fixture, &["--format", "json"])).unwrap();
+    assert_eq!(bom["dependencies"][0]["version"], "2.0.0");
+    assert_eq!(bom["dependencies"][0]["other_versions"][0], "1.0.0");
+}
+
+#[test]
+fn merged_workspaces_keep_every_version() {
+    let fixture = Fixture::new("versions");
+    let app = fixture.path("app");
+    let app2 = fixture.path("app2");
+
+    for manifests in [[&app, &app2], [&app2, &app]] {
+        let table = run(
+            &fixture,
+            &[
+                "--manifest-path",
+                manifests[0].to_str().unwrap(),
+                "--manifest-path",
+                manifests[1].to_str().unwrap(),
+            ],
+        );
+        assert!(
+            table.contains("│ dup  │ 1.0.0, 2.0.0 │ MIT      │"),
+            "{manifests:?}:\n{table}"
+        );
+    }
+}
+
+#[test]
+fn package_formats_keep_every_version() {
+    let fixture = Fixture::new("versions");
+
+    let bom: Value = serde_json::from_str(&run(&fixture, &["--format", "cyclonedx"])).unwrap();
+    let purls: Vec<&str> = bom["components"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|component| component["purl"].as_str().unwrap())
+        .collect();
+    assert_eq!(purls, ["pkg:cargo/dup@1.0.0", "pkg:cargo/dup@2.0.0"]);
+
+    let document = run(&fixture, &["--format", "spdx"]);
+    assert!(document.contains("SPDXID: SPDXRef-Package-dup-1.0.0"));
+    assert!(document.contains("SPDXID: SPDXRef-Package-dup-2.0.0"));
+
+    let result: Value = serde_json::from_str(&run(&fixture, &["--format", "ort"])).unwrap();
+    assert_eq!(
+        result["analyzer"]["result"]["packages"]
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let toml = run(&fixture, &["--format", "toml"]);
+    assert!(toml.contains("version = \"1.0.0\""));
+    assert!(toml.contains("version = \"2.0.0\""));
+}
+
+#[test]
+fn members_keep_every_version() {
+    let fixture = Fixture::new("versions");
+    let members = run(&fixture, &["--by-member"]);
+    assert!(members.contains("│ dup  │ 1.0.0   │ MIT      │ normal │"));
+    assert!(members.contains("│ dup  │ 2.0.0   │ MIT      │ normal │"));
+}
+
+#[test]
+fn duplicates_keep_every_version() {
+    let fixture = Fixture::new("versions");
+    let duplicates = run(&fixture, &["--show-duplicates"]);
+    assert!(duplicates.contains("│ dup  │ 1.0.0   │ MIT      │"));
+    assert!(duplicates.contains("│ dup  │ 2.0.0   │ MIT      │"));
+    assert!(duplicates.contains("1 crates appear in more than one version"));
+}
+
+#[test]
+fn diff_across_strategies() {
+    let fixture = Fixture::new("versions");
+    let snapshot = fixture.path("bom.json");
+    let snapshot = snapshot.to_str().unwrap();
+
+    for (written, compared) in [("name", "name-version"), ("name-version", "name")] {
+        let output = fixture.run(&[
+            "--include-path-dependencies",
+            "--dedup-strategy",
+            written,
+            "--format",
+            "json",
+            "--output",
+            snapshot,
+        ]);
+        assert!(output.status.success(), "{}", stderr(&output));
+
+        let output = fixture.run(&[
+            "--include-path-dependencies",
+            "--dedup-strategy",
+            compared,
+            "--diff",
+            snapshot,
+        ]);
+        assert!(output.status.success(), "{}", stderr(&output));
+        assert!(stdout(&output).contains("No changes since the snapshot"));
+    }
+}
@@ -0,0 +1,192 @@
+mod common;
+
+use std::fs;
+use std::process::Stdio;
+
+use common::{stderr, Fixture};
+
+#[test]
+fn usage_error_is_a_failure() {
+    let fixture = Fixture::new("licenses");
+    let output = fixture.run(&["--no-such-option"]);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr(&output));
+}
+
+#[test]
+fn policy_violation() {
+    let fixture = Fixture::new("licenses");
+    let output = fixture.run(&[
+        "--include-path-dependencies",
+        "--deny",
+        "MIT",
+        "--deny",
+        "Apache-2.0",
+    ]);
+    assert_eq!(output.status.code(), Some(2), "{}", stderr(&output));
+}
+
+#[test]
+fn check_failure_without_backtrace() {
+    let fixture = Fixture::new("licenses");
+    let output = fixture
+        .command("app")
+        .args([
+            "--include-path-dependencies",
+            "--deny",
+            "MIT",
+            "--deny",
+            "Apache-2.0",
+        ])
+        .env("RUST_BACKTRACE", "1")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2), "{}", stderr(&output));
+    assert!(
+        !stderr(&output).contains("backtrace"),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn missing_license() {
+    let fixture = Fixture::new("licenses");
+    let manifest = fixture.path("dual/Cargo.toml");
+    let contents = fs::read_to_string(&manifest).unwrap();
+    fs::write(
+        &manifest,
+        contents.replace("license = \"MIT OR Apache-2.0\"\n", ""),
+    )
+    .unwrap();
+    fs::remove_file(fixture.path("dual/LICENSE-MIT")).unwrap();
+    fs::remove_file(fixture.path("dual/LICENSE-APACHE")).unwrap();
+
+    let output = fixture.run(&["--include-path-dependencies", "--fail-on-missing-license"]);
+    assert_eq!(output.status.code(), Some(3), "{}", stderr(&output));
+}
+
+#[test]
+fn invalid_spdx() {
+    let fixture = Fixture::new("legacy-licenses");
+    let output = fixture.run(&["--include-path-dependencies", "--validate-spdx"]);
+    assert_eq!(output.status.code(), Some(4), "{}", stderr(&output));
+}
+
+#[test]
+fn changed_since_snapshot() {
+    let fixture = Fixture::new("licenses");
+    let snapshot = fixture.path("bom.json");
+    let output = fixture.run(&[
+        "--include-path-dependencies",
+        "--format",
+        "json",
+        "--output",
+        snapshot.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = fixture.run(&[
+        "--include-path-dependencies",
+        "--diff",
+        snapshot.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let manifest = fixture.path("dual/Cargo.toml");
+    let contents = fs::read_to_string(&manifest).unwrap();
+    fs::write(&manifest, contents.replace("1.0.0", "1.1.0")).unwrap();
+
+    let output = fixture.run(&[
+        "--include-path-dependencies",
+        "--diff",
+        snapshot.to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(6), "{}", stderr(&output));
+}
+
+#[test]
+fn verify_out_of_date() {
+    let fixture = Fixture::new("licenses");
+    let bom = fixture.path("BOM.txt");
+    fs::write(&bom, "stale\n").unwrap();
+
+    let output = fixture.run(&[
+        "--include-path-dependencies",
+        "verify",
+        "--against",
+        bom.to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(6), "{}", stderr(&output));
+}
+
+#[test]
+fn verify_writes_no_output() {
+    let fixture = Fixture::new("licenses");
+    let bom = fixture.path("BOM.txt");
+    fs::write(&bom, "stale\n").unwrap();
+
+    for option in ["--output", "--json-output"] {
+        let output = fixture.run(&[
+            option,
+            "out.txt",
+            "verify",
+            "--against",
+            bom.to_str().unwrap(),
+        ]);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(
+            stderr(&output).contains(&format!("{option} can't be used with verify")),
+            "{}",
+            stderr(&output)
+        );
+    }
+    assert!(!fixture.path("app/out.txt").exists());
+}
+
+#[test]
+fn closed_stdout_keeps_the_status() {
+    let fixture = Fixture::new("licenses");
+    let bom = fixture.path("BOM.txt");
+    fs::write(&bom, "stale\n".repeat(10_000)).unwrap();
+
+    for (args, status) in [
+        (&["--include-path-dependencies"][..], 0),
+        (
+            &[
+                "--include-path-dependencies",
+                "--deny",
+                "MIT",
+                "--deny",
+                "Apache-2.0",
+            ],
+            2,
+        ),
+        (
+            &[
+                "--include-path-dependencies",
+                "verify",
+                "--against",
+                bom.to_str().unwrap(),
+            ],
+            6,
+        ),
+    ] {
+        // Like `cargo bom | head`, the reader is gone before anything is written
+        let mut child = fixture
+            .command("app")
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        drop(child.stdout.take());
+
+        let output = child.wait_with_output().unwrap();
+        assert_eq!(output.status.code(), Some(status), "{}", stderr(&output));
+        assert!(
+            !stderr(&output).contains("Broken pipe"),
+            "{}",
+            stderr(&output)
+        );
+    }
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use std::path::Path;
+
+use common::{stderr, stdout, Fixture};
+use serde_json::Value;
+
+fn assert_matches_schema(fixture: &Fixture, args: &[&str]) {
+    let schema_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("schema/bom.schema.json");
+    let schema: Value =
+        serde_json::from_str(&std::fs::read_to_string(schema_path).unwrap()).unwrap();
+    let validator = jsonschema::validator_for(&schema).unwrap();
+
+    let output = fixture.run(&[&["--format", "json"], args].concat());
+    assert!(output.status.success(), "{}", stderr(&output));
+    let bom: Value = serde_json::from_str(&stdout(&output)).unwrap();
+
+    let errors: Vec<String> = validator
+        .iter_errors(&bom)
+        .map(|err| format!("{}: {err}", err.instance_path()))
+        .collect();
+    assert!(errors.is_empty(), "{args:?}: {errors:#?}");
+}
+
+#[test]
+fn output_matches_schema() {
+    let fixture = Fixture::new("licenses");
+    assert_matches_schema(&fixture, &["--include-path-dependencies"]);
+    assert_matches_schema(
+        &fixture,
+        &["--include-path-dependencies", "--embed-license-text"],
+    );
+}
+
+#[test]
+fn every_kind_of_license_matches_schema() {
+    let fixture = Fixture::new("legacy-licenses");
+    assert_matches_schema(&fixture, &["--include-path-dependencies"]);
+}
+
+#[test]
+fn workspace_members_match_schema() {
+    let fixture = Fixture::new("workspace");
+    assert_matches_schema(
+        &fixture,
+        &[
+            "--include-path-dependencies",
+            "--include-workspace-members",
+            "--kind",
+            "all",
+        ],
+    );
+}
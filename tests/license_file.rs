@@ -0,0 +1,125 @@
+mod common;
+
+use std::fs;
+
+use cargo_bom::{package_license_files, LicenseFileOptions};
+use cargo_metadata::camino::Utf8PathBuf;
+use common::{stderr, stdout, Fixture};
+
+fn metadata(fixture: &Fixture) -> cargo_metadata::Metadata {
+    cargo_metadata::MetadataCommand::new()
+        .manifest_path(fixture.path("app/Cargo.toml"))
+        .other_options(["--offline".to_string()])
+        .exec()
+        .unwrap()
+}
+
+fn canonical(fixture: &Fixture, path: &str) -> Utf8PathBuf {
+    Utf8PathBuf::from_path_buf(fixture.path(path).canonicalize().unwrap()).unwrap()
+}
+
+#[test]
+fn declared_files_in_subdirectories_and_parents_are_read() {
+    let fixture = Fixture::new("license-file");
+    let output = fixture.run(&["--include-path-dependencies"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let stdout = stdout(&output);
+    assert!(stdout.contains(
+        "-----BEGIN nested 1.0.0 LICENSES-----\nCustom license text\n\n-----END nested 1.0.0 LICENSES-----"
+    ));
+    assert!(stdout.contains(
+        "-----BEGIN parent 1.0.0 LICENSES-----\nMonorepo license text\n\n-----END parent 1.0.0 LICENSES-----"
+    ));
+    assert!(!stderr(&output).contains("warning"), "{}", stderr(&output));
+}
+
+#[test]
+fn declared_files_are_relative_to_renamed_manifests() {
+    let fixture = Fixture::new("license-file");
+    let metadata = metadata(&fixture);
+    let options = LicenseFileOptions::default();
+
+    for (name, dir, license_file) in [
+        ("nested", "nested", "nested/docs/LICENSE.custom"),
+        ("parent", "monorepo/parent", "monorepo/LICENSE"),
+    ] {
+        let mut package = metadata
+            .packages
+            .iter()
+            .find(|package| package.name == name)
+            .unwrap()
+            .clone();
+
+        // As with crates whose manifest has another name, only its directory
+        // tells where the license file is
+        let renamed = fixture.path(dir).join(format!("{name}.toml"));
+        fs::rename(fixture.path(dir).join("Cargo.toml"), &renamed).unwrap();
+        package.manifest_path = Utf8PathBuf::from_path_buf(renamed).unwrap();
+
+        let files = package_license_files(&package, &options, &mut Vec::new()).unwrap();
+        assert_eq!(
+            files.into_iter().collect::<Vec<_>>(),
+            [canonical(&fixture, license_file)],
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn license_dir_keeps_files_of_the_same_name_apart() {
+    let fixture = Fixture::new("license-file");
+    fs::write(fixture.path("nested/LICENSE"), "Top-level license text\n").unwrap();
+    fs::write(
+        fixture.path("nested/docs/LICENSE"),
+        "Documentation license text\n",
+    )
+    .unwrap();
+    let manifest = fixture.path("nested/Cargo.toml");
+    let contents = fs::read_to_string(&manifest).unwrap();
+    fs::write(&manifest, contents.replace("LICENSE.custom", "LICENSE")).unwrap();
+
+    let dir = fixture.path("licenses");
+    let output = fixture.run(&[
+        "--include-path-dependencies",
+        "--license-dir",
+        dir.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    assert_eq!(
+        fs::read_to_string(dir.join("nested-1.0.0/LICENSE")).unwrap(),
+        "Top-level license text\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("nested-1.0.0/docs/LICENSE")).unwrap(),
+        "Documentation license text\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("MANIFEST.txt")).unwrap(),
+        "nested 1.0.0: nested-1.0.0/LICENSE\n\
+         nested 1.0.0: nested-1.0.0/docs/LICENSE\n\
+         parent 1.0.0: parent-1.0.0/LICENSE\n"
+    );
+}
+
+#[test]
+fn license_dir_skips_files_that_cant_be_copied() {
+    let fixture = Fixture::new("licenses");
+    let dir = fixture.path("licenses");
+    // A directory in the way of one of the copies
+    fs::create_dir_all(dir.join("dual-1.0.0/LICENSE-MIT")).unwrap();
+
+    let output = fixture.run(&[
+        "--include-path-dependencies",
+        "--license-dir",
+        dir.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("warning: failed to read license file of dual 1.0.0: "));
+
+    assert_eq!(
+        fs::read_to_string(dir.join("MANIFEST.txt")).unwrap(),
+        "dual 1.0.0: dual-1.0.0/LICENSE-APACHE\n"
+    );
+}
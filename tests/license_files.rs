@@ -0,0 +1,64 @@
+mod common;
+
+use std::fs;
+
+use common::{stderr, stdout, Fixture};
+
+/// Turn the Apache license of `dual` into a directory, so it's still found
+/// but can't be read.
+fn unreadable_apache_license(fixture: &Fixture) {
+    let license = fixture.path("dual/LICENSE-APACHE");
+    fs::remove_file(&license).unwrap();
+    fs::create_dir(&license).unwrap();
+
+    let manifest = fixture.path("dual/Cargo.toml");
+    let mut contents = fs::read_to_string(&manifest).unwrap();
+    contents.push_str("license-file = \"LICENSE-APACHE\"\n");
+    fs::write(manifest, contents).unwrap();
+}
+
+#[test]
+fn unreadable_license_file_does_not_shift_texts() {
+    let fixture = Fixture::new("licenses");
+    unreadable_apache_license(&fixture);
+
+    let output = fixture.run(&["--include-path-dependencies", "--attribution"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("warning: failed to read license file of dual 1.0.0"));
+
+    let stdout = stdout(&output);
+    assert!(stdout.contains("MIT (LICENSE-MIT):\n\nMIT License text"));
+    assert!(!stdout.contains("LICENSE-APACHE"));
+}
+
+#[test]
+fn unreadable_license_file_is_skipped_in_spdx() {
+    let fixture = Fixture::new("licenses");
+    unreadable_apache_license(&fixture);
+    let manifest = fixture.path("dual/Cargo.toml");
+    let contents = fs::read_to_string(&manifest).unwrap();
+    fs::write(
+        &manifest,
+        contents.replace("license = \"MIT OR Apache-2.0\"\n", ""),
+    )
+    .unwrap();
+
+    let output = fixture.run(&["--include-path-dependencies", "--format", "spdx"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("warning: failed to read license file of dual 1.0.0"));
+    assert!(stdout(&output).contains("PackageName: dual"));
+}
+
+#[test]
+fn attribution_licenses_are_canonical() {
+    let fixture = Fixture::new("legacy-licenses");
+    let output = fixture.run(&["--include-path-dependencies", "--attribution"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let stdout = stdout(&output);
+    assert!(stdout.contains("License: MIT OR Apache-2.0\n"), "{stdout}");
+    assert!(
+        stdout.contains("License: GPL-2.0-or-later AND MIT\n"),
+        "{stdout}"
+    );
+}
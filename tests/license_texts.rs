@@ -0,0 +1,170 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use cargo_bom::{write_license_texts, Delimiters, LicenseTable, LicenseTextOptions};
+use cargo_metadata::camino::Utf8PathBuf;
+
+const TEXTS: [&str; 3] = ["first\n", "second\n", "third\n"];
+
+/// A crate with license files `LICENSE-0` up to `LICENSE-{count - 1}`.
+fn crate_with_files(dir: &Path, name: &str, count: usize) -> LicenseTable {
+    let crate_dir = dir.join(name);
+    fs::create_dir(&crate_dir).unwrap();
+
+    let license_files = TEXTS[..count]
+        .iter()
+        .enumerate()
+        .map(|(i, text)| {
+            let path = crate_dir.join(format!("LICENSE-{i}"));
+            fs::write(&path, text).unwrap();
+            Utf8PathBuf::from_path_buf(path).unwrap()
+        })
+        .collect();
+
+    LicenseTable {
+        name: name.to_string(),
+        version: "1.0.0".to_string(),
+        license_files,
+    }
+}
+
+fn render(licenses_list: &BTreeSet<LicenseTable>, delimiters: Delimiters) -> String {
+    let mut out = Vec::new();
+    let options = LicenseTextOptions {
+        delimiters,
+        ..LicenseTextOptions::default()
+    };
+    write_license_texts(&mut out, licenses_list, options).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn armored_separators() {
+    let dir = tempfile::tempdir().unwrap();
+    let licenses_list = BTreeSet::from([
+        crate_with_files(dir.path(), "one", 1),
+        crate_with_files(dir.path(), "three", 3),
+        crate_with_files(dir.path(), "two", 2),
+    ]);
+
+    assert_eq!(
+        render(&licenses_list, Delimiters::Armored),
+        "\n-----BEGIN one 1.0.0 LICENSES-----\n\
+         first\n\
+         \n-----END one 1.0.0 LICENSES-----\n\
+         \n-----BEGIN three 1.0.0 LICENSES-----\n\
+         first\n\
+         \n-----NEXT LICENSE-----\n\
+         second\n\
+         \n-----NEXT LICENSE-----\n\
+         third\n\
+         \n-----END three 1.0.0 LICENSES-----\n\
+         \n-----BEGIN two 1.0.0 LICENSES-----\n\
+         first\n\
+         \n-----NEXT LICENSE-----\n\
+         second\n\
+         \n-----END two 1.0.0 LICENSES-----\n"
+    );
+}
+
+#[test]
+fn one_separator_less_than_files() {
+    let dir = tempfile::tempdir().unwrap();
+    for count in 1..=3 {
+        let name = format!("crate{count}");
+        let licenses_list = BTreeSet::from([crate_with_files(dir.path(), &name, count)]);
+        let rendered = render(&licenses_list, Delimiters::Armored);
+
+        assert_eq!(
+            rendered.matches("-----NEXT LICENSE-----").count(),
+            count - 1
+        );
+        assert_eq!(rendered.matches("-----BEGIN ").count(), 1);
+        assert_eq!(rendered.matches("-----END ").count(), 1);
+        assert!(rendered.ends_with(&format!(
+            "{}\n-----END {name} 1.0.0 LICENSES-----\n",
+            TEXTS[count - 1]
+        )));
+    }
+}
+
+#[test]
+fn headings_name_each_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let licenses_list = BTreeSet::from([
+        crate_with_files(dir.path(), "one", 1),
+        crate_with_files(dir.path(), "two", 2),
+    ]);
+
+    assert_eq!(
+        render(&licenses_list, Delimiters::Headings),
+        "\n=== one 1.0.0: LICENSE-0 ===\n\n\
+         first\n\
+         \n=== two 1.0.0: LICENSE-0 ===\n\n\
+         first\n\
+         \n=== two 1.0.0: LICENSE-1 ===\n\n\
+         second\n"
+    );
+}
+
+#[test]
+fn headings_end_texts_with_a_newline() {
+    let dir = tempfile::tempdir().unwrap();
+    let table = crate_with_files(dir.path(), "one", 1);
+    fs::write(table.license_files.first().unwrap(), "no newline").unwrap();
+    let licenses_list = BTreeSet::from([table]);
+
+    assert_eq!(
+        render(&licenses_list, Delimiters::Headings),
+        "\n=== one 1.0.0: LICENSE-0 ===\n\nno newline\n"
+    );
+}
+
+#[test]
+fn crates_without_files_print_nothing() {
+    let dir = tempfile::tempdir().unwrap();
+    let licenses_list = BTreeSet::from([crate_with_files(dir.path(), "none", 0)]);
+
+    for delimiters in [Delimiters::Armored, Delimiters::Headings] {
+        assert_eq!(render(&licenses_list, delimiters), "");
+    }
+}
+
+#[test]
+fn deduplicated_texts_list_their_crates() {
+    let dir = tempfile::tempdir().unwrap();
+    let licenses_list = BTreeSet::from([
+        crate_with_files(dir.path(), "one", 1),
+        crate_with_files(dir.path(), "two", 2),
+    ]);
+    let render = |delimiters| {
+        let mut out = Vec::new();
+        let options = LicenseTextOptions {
+            dedup: true,
+            delimiters,
+            ..LicenseTextOptions::default()
+        };
+        write_license_texts(&mut out, &licenses_list, options).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+
+    assert_eq!(
+        render(Delimiters::Armored),
+        "\n-----BEGIN LICENSE-----\n\
+         Used by: one 1.0.0, two 1.0.0\n\n\
+         first\n\
+         \n-----END LICENSE-----\n\
+         \n-----BEGIN LICENSE-----\n\
+         Used by: two 1.0.0\n\n\
+         second\n\
+         \n-----END LICENSE-----\n"
+    );
+    assert_eq!(
+        render(Delimiters::Headings),
+        "\n=== one 1.0.0, two 1.0.0 ===\n\n\
+         first\n\
+         \n=== two 1.0.0 ===\n\n\
+         second\n"
+    );
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use std::fs;
+
+use common::{stderr, stdout, Fixture};
+
+#[test]
+fn table_views_conflict_with_other_formats() {
+    let fixture = Fixture::new("licenses");
+    for option in [
+        "--attribution",
+        "--show-duplicates",
+        "--group-by-license",
+        "--by-member",
+        "--highlight-copyleft",
+    ] {
+        let output = fixture.run(&["--format", "json", option]);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(
+            stderr(&output).contains(&format!("{option} can't be used with --format json")),
+            "{}",
+            stderr(&output)
+        );
+    }
+}
+
+#[test]
+fn table_views_conflict_with_configured_format() {
+    let fixture = Fixture::new("licenses");
+    let manifest = fixture.path("app/Cargo.toml");
+    let mut contents = fs::read_to_string(&manifest).unwrap();
+    contents.push_str("\n[workspace.metadata.cargo-bom]\nformat = \"csv\"\n");
+    fs::write(&manifest, contents).unwrap();
+
+    let output = fixture.run(&["--attribution"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("--attribution can't be used with --format csv"));
+
+    let output = fixture.run(&["--format", "table", "--attribution"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+}
+
+#[test]
+fn package_from_any_merged_workspace() {
+    let fixture = Fixture::new("versions");
+    let app = fixture.path("app");
+    let app2 = fixture.path("app2");
+    let args = [
+        "--include-path-dependencies",
+        "--manifest-path",
+        app.to_str().unwrap(),
+        "--manifest-path",
+        app2.to_str().unwrap(),
+        "--package",
+    ];
+
+    let output = fixture.run(&[&args[..], &["app2"]].concat());
+    assert!(output.status.success(), "{}", stderr(&output));
+    let table = stdout(&output);
+    assert!(table.contains("2.0.0"), "{table}");
+    assert!(!table.contains("1.0.0"), "{table}");
+
+    let output = fixture.run(&[&args[..], &["missing"]].concat());
+    assert_eq!(output.status.code(), Some(1));
+    let expected = format!(
+        "package `missing` is not a member of the workspace of {}, {}",
+        app.join("Cargo.toml").display(),
+        app2.join("Cargo.toml").display()
+    );
+    assert!(stderr(&output).contains(&expected), "{}", stderr(&output));
+}
+
+#[test]
+fn highlight_copyleft_needs_the_table() {
+    let fixture = Fixture::new("licenses");
+    for option in ["--attribution", "--by-member"] {
+        let output = fixture.run(&["--highlight-copyleft", option]);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(
+            stderr(&output).contains(&format!(
+                "the argument '--highlight-copyleft' cannot be used with '{option}'"
+            )),
+            "{}",
+            stderr(&output)
+        );
+    }
+}
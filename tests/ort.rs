@@ -0,0 +1,53 @@
+mod common;
+
+use common::{stderr, stdout, Fixture};
+use serde_json::{json, Value};
+
+#[test]
+fn projects_only_list_their_own_dependencies() {
+    let fixture = Fixture::new("workspace");
+    let output = fixture.run(&[
+        "--include-path-dependencies",
+        "--kind",
+        "all",
+        "--format",
+        "ort",
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let result: Value = serde_json::from_str(&stdout(&output)).unwrap();
+
+    let projects = &result["analyzer"]["result"]["projects"];
+    assert_eq!(projects[0]["id"], "Cargo::a:0.1.0");
+    assert_eq!(
+        projects[0]["scopes"],
+        json!([{ "name": "dependencies", "dependencies": [{ "id": "Crate::x:1.0.0" }] }])
+    );
+    assert_eq!(projects[1]["id"], "Cargo::b:0.1.0");
+    assert_eq!(
+        projects[1]["scopes"],
+        json!([
+            { "name": "dependencies", "dependencies": [{ "id": "Crate::y:1.0.0" }] },
+            { "name": "dev-dependencies", "dependencies": [{ "id": "Crate::x:1.0.0" }] },
+        ])
+    );
+}
+
+#[test]
+fn processed_licenses_are_canonical() {
+    let fixture = Fixture::new("legacy-licenses");
+    let output = fixture.run(&["--include-path-dependencies", "--format", "ort"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let result: Value = serde_json::from_str(&stdout(&output)).unwrap();
+
+    let packages = &result["analyzer"]["result"]["packages"];
+    assert_eq!(packages[0]["id"], "Crate::deprecated:1.0.0");
+    assert_eq!(
+        packages[0]["declared_licenses_processed"]["spdx_expression"],
+        "GPL-2.0-or-later AND MIT"
+    );
+    assert_eq!(packages[2]["id"], "Crate::slash:1.0.0");
+    assert_eq!(
+        packages[2]["declared_licenses_processed"]["spdx_expression"],
+        "MIT OR Apache-2.0"
+    );
+}
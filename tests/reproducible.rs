@@ -0,0 +1,54 @@
+mod common;
+
+use std::fs;
+
+use common::{stderr, Fixture};
+
+const FILES: [&str; 4] = ["COPYING", "LICENSE", "LICENSE-APACHE", "LICENSE-MIT"];
+
+/// The `licenses` fixture with its license files created in the given
+/// order, which is the order many file systems list them in.
+fn fixture_with_files_in_order(files: &[&str]) -> Fixture {
+    let fixture = Fixture::new("licenses");
+    for file in FILES {
+        let _ = fs::remove_file(fixture.path("dual").join(file));
+    }
+    for file in files {
+        fs::write(fixture.path("dual").join(file), format!("{file} text\n")).unwrap();
+    }
+    fixture
+}
+
+fn output(fixture: &Fixture, args: &[&str]) -> Vec<u8> {
+    let output = fixture.run(&[&["--include-path-dependencies"], args].concat());
+    assert!(output.status.success(), "{}", stderr(&output));
+    output.stdout
+}
+
+#[test]
+fn output_is_the_same_across_runs_and_directory_order() {
+    let forward = fixture_with_files_in_order(&FILES);
+    let mut reversed = FILES;
+    reversed.reverse();
+    let backward = fixture_with_files_in_order(&reversed);
+
+    for args in [
+        &["--format", "table"][..],
+        &["--format", "table", "--delimiters", "headings"],
+        &["--format", "table", "--attribution"],
+        &["--format", "json", "--embed-license-text"],
+        &["--format", "spdx"],
+    ] {
+        let first = output(&forward, args);
+        assert_eq!(
+            first,
+            output(&forward, args),
+            "{args:?} differs between runs"
+        );
+        assert_eq!(
+            first,
+            output(&backward, args),
+            "{args:?} depends on directory order"
+        );
+    }
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::{stderr, stdout, Fixture};
+
+fn package_lines(document: &str, name: &str) -> Vec<String> {
+    document
+        .split("\n\n")
+        .find(|section| section.contains(&format!("PackageName: {name}\n")))
+        .unwrap_or_else(|| panic!("no package {name} in:\n{document}"))
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn declared_licenses_are_canonical_spdx() {
+    let fixture = Fixture::new("legacy-licenses");
+    let output = fixture.run(&["--include-path-dependencies", "--format", "spdx"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let document = stdout(&output);
+
+    let slash = package_lines(&document, "slash");
+    assert!(slash.contains(&"PackageLicenseDeclared: MIT OR Apache-2.0".to_string()));
+
+    let deprecated = package_lines(&document, "deprecated");
+    assert!(deprecated.contains(&"PackageLicenseDeclared: GPL-2.0-or-later AND MIT".to_string()));
+}
+
+#[test]
+fn free_form_license_is_no_assertion() {
+    let fixture = Fixture::new("legacy-licenses");
+    let output = fixture.run(&["--include-path-dependencies", "--format", "spdx"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let freeform = package_lines(&stdout(&output), "freeform");
+    assert!(freeform.contains(&"PackageLicenseConcluded: NOASSERTION".to_string()));
+    assert!(freeform.contains(&"PackageLicenseDeclared: NOASSERTION".to_string()));
+    assert!(freeform.contains(
+        &"PackageLicenseComments: <text>Declared license: Proprietary, all rights reserved</text>"
+            .to_string()
+    ));
+}
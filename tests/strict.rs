@@ -0,0 +1,31 @@
+mod common;
+
+use common::{stderr, Fixture};
+
+#[test]
+fn passes_on_well_maintained_dependencies() {
+    let fixture = Fixture::new("licenses");
+    let output = fixture.run(&["--include-path-dependencies", "--strict"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+}
+
+#[test]
+fn reports_every_problem() {
+    let fixture = Fixture::new("legacy-licenses");
+    let output = fixture.run(&["--include-path-dependencies", "--strict"]);
+
+    // Missing license texts take precedence over invalid expressions
+    assert_eq!(output.status.code(), Some(3));
+
+    let stderr = stderr(&output);
+    for message in [
+        "warning: slash 1.0.0: \"MIT/Apache-2.0\" isn't valid SPDX as written",
+        "missing license text: slash 1.0.0 (Apache-2.0, MIT)",
+        "missing license text: freeform 1.0.0 (Proprietary, all rights reserved)",
+        "invalid license expression: freeform 1.0.0: \"Proprietary, all rights reserved\": unknown term",
+        "Error: 3 dependencies ship no license text, 1 dependencies have an invalid SPDX license expression",
+    ] {
+        assert!(stderr.contains(message), "{message:?} not in:\n{stderr}");
+    }
+    assert!(!stderr.contains("invalid license expression: slash"));
+}
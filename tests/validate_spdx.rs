@@ -0,0 +1,36 @@
+mod common;
+
+use common::{stderr, Fixture};
+
+#[test]
+fn lenient_expressions_are_warnings() {
+    let fixture = Fixture::new("legacy-licenses");
+    let output = fixture.run(&[
+        "--include-path-dependencies",
+        "--validate-spdx",
+        "--exclude",
+        "freeform",
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let stderr = stderr(&output);
+    assert!(stderr.contains(
+        "warning: slash 1.0.0: \"MIT/Apache-2.0\" isn't valid SPDX as written, it means \"MIT OR Apache-2.0\""
+    ));
+    assert!(stderr.contains(
+        "warning: deprecated 1.0.0: \"GPL-2.0+ AND MIT\" isn't valid SPDX as written, it means \"GPL-2.0-or-later AND MIT\""
+    ));
+}
+
+#[test]
+fn malformed_expressions_fail() {
+    let fixture = Fixture::new("legacy-licenses");
+    let output = fixture.run(&["--include-path-dependencies", "--validate-spdx"]);
+    assert_eq!(output.status.code(), Some(4));
+
+    let stderr = stderr(&output);
+    assert!(stderr.contains(
+        "invalid license expression: freeform 1.0.0: \"Proprietary, all rights reserved\""
+    ));
+    assert!(stderr.contains("1 dependencies have an invalid SPDX license expression"));
+}
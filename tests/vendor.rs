@@ -0,0 +1,50 @@
+mod common;
+
+use std::fs;
+use std::process::Output;
+
+use common::{stderr, stdout, Fixture};
+use serde_json::Value;
+
+/// Run with an empty `CARGO_HOME`, so nothing can come from the registry
+/// cache.
+fn run(fixture: &Fixture, args: &[&str]) -> Output {
+    let output = fixture
+        .command("app")
+        .args(args)
+        .env("CARGO_HOME", fixture.path("cargo-home"))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    output
+}
+
+#[test]
+fn license_files_come_from_the_vendor_directory() {
+    let fixture = Fixture::new("vendor");
+    let output = run(&fixture, &[]);
+    assert!(stdout(&output)
+        .contains("-----BEGIN vendored-dep 1.2.3 LICENSES-----\nVendored MIT license text\n"));
+
+    let license_dir = fixture.path("licenses");
+    run(&fixture, &["--license-dir", license_dir.to_str().unwrap()]);
+    assert_eq!(
+        fs::read_to_string(license_dir.join("vendored-dep-1.2.3/LICENSE-MIT")).unwrap(),
+        "Vendored MIT license text\n"
+    );
+}
+
+#[test]
+fn source_is_where_the_crate_was_vendored_from() {
+    let fixture = Fixture::new("vendor");
+    let output = run(&fixture, &["--format", "json"]);
+    let bom: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(
+        bom["dependencies"][0]["source"],
+        "registry+https://github.com/rust-lang/crates.io-index"
+    );
+
+    let output = run(&fixture, &["--format", "cyclonedx"]);
+    let bom: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(bom["components"][0]["purl"], "pkg:cargo/vendored-dep@1.2.3");
+}